@@ -1,18 +1,134 @@
-use crate::values::{Point, N};
+use crate::board::Board;
+use crate::rng::Rng;
+use crate::values::{Point, Transform, N};
 use std::fmt;
 use std::mem;
 use std::ops;
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 pub enum Action {
     UP,
     DOWN,
     LEFT,
     RIGHT,
+    #[cfg(feature = "diagonals")]
+    UPLEFT,
+    #[cfg(feature = "diagonals")]
+    UPRIGHT,
+    #[cfg(feature = "diagonals")]
+    DOWNLEFT,
+    #[cfg(feature = "diagonals")]
+    DOWNRIGHT,
 }
 
+#[cfg(not(feature = "diagonals"))]
 pub const ACTIONS: [Action; 4] = [Action::UP, Action::DOWN, Action::LEFT, Action::RIGHT];
 
+/**
+   With the `diagonals` feature the cursor may also move diagonally and the move sweeps
+   the diagonal line. Supported by board moves (also wrapping), `is_lost`, `heuristic`,
+   `predecessors`, symmetries, `ActionSequence` and the solvers. The move letters and
+   therefore replays and `--format` output use `Q`, `E`, `Z` and `C` for the diagonals.
+   The tests of the orthogonal game assume the feature is off.
+*/
+#[cfg(feature = "diagonals")]
+pub const ACTIONS: [Action; 8] = [
+    Action::UP,
+    Action::DOWN,
+    Action::LEFT,
+    Action::RIGHT,
+    Action::UPLEFT,
+    Action::UPRIGHT,
+    Action::DOWNLEFT,
+    Action::DOWNRIGHT,
+];
+
+impl Action {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Action::UP => Action::DOWN,
+            Action::DOWN => Action::UP,
+            Action::LEFT => Action::RIGHT,
+            Action::RIGHT => Action::LEFT,
+            #[cfg(feature = "diagonals")]
+            Action::UPLEFT => Action::DOWNRIGHT,
+            #[cfg(feature = "diagonals")]
+            Action::UPRIGHT => Action::DOWNLEFT,
+            #[cfg(feature = "diagonals")]
+            Action::DOWNLEFT => Action::UPRIGHT,
+            #[cfg(feature = "diagonals")]
+            Action::DOWNRIGHT => Action::UPLEFT,
+        }
+    }
+
+    /**
+       The direction this action points to after transforming the board by `t`.
+    */
+    pub fn symmetry(&self, t: Transform) -> Self {
+        let mut action = match (t.mirrored(), self) {
+            (true, Action::LEFT) => Action::RIGHT,
+            (true, Action::RIGHT) => Action::LEFT,
+            #[cfg(feature = "diagonals")]
+            (true, Action::UPLEFT) => Action::UPRIGHT,
+            #[cfg(feature = "diagonals")]
+            (true, Action::UPRIGHT) => Action::UPLEFT,
+            #[cfg(feature = "diagonals")]
+            (true, Action::DOWNLEFT) => Action::DOWNRIGHT,
+            #[cfg(feature = "diagonals")]
+            (true, Action::DOWNRIGHT) => Action::DOWNLEFT,
+            _ => *self,
+        };
+        for _ in 0..t.quarter_turns() {
+            // Clockwise rotation
+            action = match action {
+                Action::UP => Action::RIGHT,
+                Action::RIGHT => Action::DOWN,
+                Action::DOWN => Action::LEFT,
+                Action::LEFT => Action::UP,
+                #[cfg(feature = "diagonals")]
+                Action::UPLEFT => Action::UPRIGHT,
+                #[cfg(feature = "diagonals")]
+                Action::UPRIGHT => Action::DOWNRIGHT,
+                #[cfg(feature = "diagonals")]
+                Action::DOWNRIGHT => Action::DOWNLEFT,
+                #[cfg(feature = "diagonals")]
+                Action::DOWNLEFT => Action::UPLEFT,
+            };
+        }
+        action
+    }
+
+    pub fn reverse_symmetry(&self, t: Transform) -> Self {
+        self.symmetry(t.reverse())
+    }
+
+    /**
+       The first letter of the direction: `U`, `D`, `L` or `R`.
+       The diagonals use the keys around `S` on a keyboard: `Q`, `E`, `Z` and `C`.
+    */
+    pub fn letter(&self) -> char {
+        match self {
+            Action::UP => 'U',
+            Action::DOWN => 'D',
+            Action::LEFT => 'L',
+            Action::RIGHT => 'R',
+            #[cfg(feature = "diagonals")]
+            Action::UPLEFT => 'Q',
+            #[cfg(feature = "diagonals")]
+            Action::UPRIGHT => 'E',
+            #[cfg(feature = "diagonals")]
+            Action::DOWNLEFT => 'Z',
+            #[cfg(feature = "diagonals")]
+            Action::DOWNRIGHT => 'C',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        ACTIONS.into_iter().find(|a| a.letter() == letter)
+    }
+}
+
 impl ops::Add<Action> for Point {
     type Output = Self;
 
@@ -24,18 +140,50 @@ impl ops::Add<Action> for Point {
                 Action::LEFT | Action::RIGHT => row,
                 Action::UP if row > 0 => row - 1,
                 Action::DOWN => row + 1,
+                #[cfg(feature = "diagonals")]
+                Action::UPLEFT | Action::UPRIGHT if row > 0 => row - 1,
+                #[cfg(feature = "diagonals")]
+                Action::DOWNLEFT | Action::DOWNRIGHT => row + 1,
                 _ => N,
             },
             match rhs {
                 Action::UP | Action::DOWN => col,
                 Action::LEFT if col > 0 => col - 1,
                 Action::RIGHT => col + 1,
+                #[cfg(feature = "diagonals")]
+                Action::UPLEFT | Action::DOWNLEFT if col > 0 => col - 1,
+                #[cfg(feature = "diagonals")]
+                Action::UPRIGHT | Action::DOWNRIGHT => col + 1,
                 _ => N,
             },
         )
     }
 }
 
+impl Point {
+    /**
+       Like `Point + Action`, but leaving the grid on one edge enters it on the opposite edge.
+    */
+    pub fn wrapping_add(self, action: Action) -> Self {
+        let row = self.row();
+        let col = self.column();
+        match action {
+            Action::UP => Point::from((row + N - 1) % N, col),
+            Action::DOWN => Point::from((row + 1) % N, col),
+            Action::LEFT => Point::from(row, (col + N - 1) % N),
+            Action::RIGHT => Point::from(row, (col + 1) % N),
+            #[cfg(feature = "diagonals")]
+            Action::UPLEFT => Point::from((row + N - 1) % N, (col + N - 1) % N),
+            #[cfg(feature = "diagonals")]
+            Action::UPRIGHT => Point::from((row + N - 1) % N, (col + 1) % N),
+            #[cfg(feature = "diagonals")]
+            Action::DOWNLEFT => Point::from((row + 1) % N, (col + N - 1) % N),
+            #[cfg(feature = "diagonals")]
+            Action::DOWNRIGHT => Point::from((row + 1) % N, (col + 1) % N),
+        }
+    }
+}
+
 impl fmt::Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let name = match self {
@@ -43,18 +191,29 @@ impl fmt::Display for Action {
             Action::DOWN => "Down",
             Action::LEFT => "Left",
             Action::RIGHT => "Right",
+            #[cfg(feature = "diagonals")]
+            Action::UPLEFT => "UpLeft",
+            #[cfg(feature = "diagonals")]
+            Action::UPRIGHT => "UpRight",
+            #[cfg(feature = "diagonals")]
+            Action::DOWNLEFT => "DownLeft",
+            #[cfg(feature = "diagonals")]
+            Action::DOWNRIGHT => "DownRight",
         };
         write!(f, "{name}")
     }
 }
 
 type Seq = u64;
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
 pub struct ActionSequence(Seq);
 const SEQ_BITS: usize = mem::size_of::<ActionSequence>() * 8;
 const LEN_BITS: usize = 6;
+#[cfg(not(feature = "diagonals"))]
 const ACTION_BITS: usize = 2;
-const ACTION_MASK: Seq = 0x03;
+#[cfg(feature = "diagonals")]
+const ACTION_BITS: usize = 3;
+const ACTION_MASK: Seq = (1 << ACTION_BITS) - 1;
 
 impl ActionSequence {
     pub const MAX_LENGTH: usize = (SEQ_BITS - LEN_BITS) / ACTION_BITS;
@@ -67,34 +226,75 @@ impl ActionSequence {
         (0x3F & self.0) as usize
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn add(self, action: Action) -> ActionSequence {
         let len = self.length();
-        debug_assert!(
-            len + 1 <= Self::MAX_LENGTH,
-            " should be lower than max length"
-        );
+        debug_assert!(len < Self::MAX_LENGTH, " should be lower than max length");
         Self(
-            self.0 ^ ((len ^ (len + 1)) as Seq)
-                | match action {
-                    Action::UP => 0,
-                    Action::DOWN => 1,
-                    Action::LEFT => 2,
-                    Action::RIGHT => 3,
-                } << (ACTION_BITS * len + LEN_BITS),
+            self.0 ^ ((len ^ (len + 1)) as Seq) | (action as Seq) << (ACTION_BITS * len + LEN_BITS),
         )
     }
 
     pub fn get(&self, index: usize) -> Action {
         ACTIONS[((self.0 >> (index * ACTION_BITS + LEN_BITS)) & ACTION_MASK) as usize]
     }
+
+    /**
+       The packed bits: the length in the lowest 6 bits, followed by 2 bits per action
+       in `ACTIONS` order, i.e. action `i` takes bits `6 + 2i` and `7 + 2i`.
+       With the `diagonals` feature each action takes 3 bits instead.
+    */
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /**
+       The sequence packed into `bits`, or None if they are not a valid sequence:
+       too long or with bits set beyond its last action.
+    */
+    pub fn from_bits(bits: u64) -> Option<Self> {
+        let seq = Self(bits);
+        let used = LEN_BITS + ACTION_BITS * seq.length();
+        (seq.length() <= Self::MAX_LENGTH && (used == SEQ_BITS || bits >> used == 0)).then_some(seq)
+    }
+
+    /**
+       Hash which stays the same across runs and platforms.
+       Different sequences never share a hash, since it mixes the packed bits bijectively.
+    */
+    pub fn stable_hash(&self) -> u64 {
+        Rng::new(self.0).next_u64()
+    }
+
+    /**
+       Like `stable_hash`, but equal for sequences which are transformed into each other
+       by a symmetry of `board`, such as the mirrored solutions of a mirror-symmetric board.
+    */
+    pub fn canonical_hash(&self, board: &Board) -> u64 {
+        let canonical = Transform::ALL
+            .into_iter()
+            .filter(|t| board.symmetry(*t) == *board)
+            .map(|t| {
+                (0..self.length())
+                    .map(|i| self.get(i).symmetry(t))
+                    .collect::<ActionSequence>()
+                    .0
+            })
+            .min()
+            .expect("identity should be a symmetry");
+        Self(canonical).stable_hash()
+    }
 }
 
 impl From<ActionSequence> for Vec<Action> {
     fn from(value: ActionSequence) -> Self {
-        (0..value.length())
-            .into_iter()
-            .map(|i| value.get(i))
-            .collect()
+        (0..value.length()).map(|i| value.get(i)).collect()
+    }
+}
+
+impl FromIterator<Action> for ActionSequence {
+    fn from_iter<I: IntoIterator<Item = Action>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), Self::add)
     }
 }
 
@@ -103,6 +303,52 @@ mod tests {
     use super::*;
     use itertools::Itertools;
 
+    #[test]
+    fn pack_bits() {
+        let seq: ActionSequence = [Action::DOWN, Action::RIGHT].into_iter().collect();
+        #[cfg(not(feature = "diagonals"))]
+        let (bits, unused) = (2 | 1 << 6 | 3 << 8, 1 << 10);
+        #[cfg(feature = "diagonals")]
+        let (bits, unused) = (2 | 1 << 6 | 3 << 9, 1 << 12);
+        assert_eq!(seq.bits(), bits);
+        assert_eq!(ActionSequence::from_bits(seq.bits()), Some(seq));
+        assert_eq!(ActionSequence::from_bits(seq.bits() | unused), None);
+        assert_eq!(ActionSequence::from_bits(30), None);
+        let full: ActionSequence = [Action::RIGHT; ActionSequence::MAX_LENGTH]
+            .into_iter()
+            .collect();
+        assert_eq!(ActionSequence::from_bits(full.bits()), Some(full));
+    }
+
+    #[test]
+    fn hash_sequences() {
+        let down_right: ActionSequence = [Action::DOWN, Action::DOWN, Action::RIGHT]
+            .into_iter()
+            .collect();
+        let again: ActionSequence = [Action::DOWN, Action::DOWN, Action::RIGHT]
+            .into_iter()
+            .collect();
+        let right_down: ActionSequence = [Action::RIGHT, Action::RIGHT, Action::DOWN]
+            .into_iter()
+            .collect();
+        assert_eq!(down_right.stable_hash(), again.stable_hash());
+        assert_ne!(down_right.stable_hash(), right_down.stable_hash());
+        let symmetric: Board = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(
+            down_right.canonical_hash(&symmetric),
+            right_down.canonical_hash(&symmetric)
+        );
+        let asymmetric: Board = "0 3 3 0|3 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(
+            down_right.canonical_hash(&asymmetric),
+            down_right.stable_hash()
+        );
+        assert_ne!(
+            down_right.canonical_hash(&asymmetric),
+            right_down.canonical_hash(&asymmetric)
+        );
+    }
+
     #[test]
     fn add_and_get_actions() {
         let mut seq = ActionSequence::new();
@@ -133,7 +379,6 @@ mod tests {
         assert_eq!(
             long_seq,
             (0..ActionSequence::MAX_LENGTH)
-                .into_iter()
                 .map(|_| Action::RIGHT)
                 .collect_vec()
         );
@@ -161,4 +406,60 @@ mod tests {
         assert!(!(point2 + Action::RIGHT + Action::RIGHT).inside());
         assert!(!(point2 + Action::DOWN + Action::DOWN).inside());
     }
+
+    #[test]
+    fn wrap_action_at_edge() {
+        let corner = Point::from(0, N - 1);
+        assert!(!(corner + Action::RIGHT).inside());
+        assert_eq!(corner.wrapping_add(Action::RIGHT), Point::from(0, 0));
+        assert_eq!(corner.wrapping_add(Action::UP), Point::from(N - 1, N - 1));
+        assert_eq!(corner.wrapping_add(Action::LEFT), corner + Action::LEFT);
+        assert_eq!(corner.wrapping_add(Action::DOWN), corner + Action::DOWN);
+    }
+
+    #[test]
+    fn transform_action() {
+        let point = Point::from(1, 2);
+        for t in Transform::ALL {
+            for a in ACTIONS {
+                assert_eq!(
+                    (point + a).symmetry(t),
+                    point.symmetry(t) + a.symmetry(t),
+                    "should move {a} consistently under {t:?}"
+                );
+                assert_eq!(a.symmetry(t).reverse_symmetry(t), a);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "diagonals")]
+    fn diagonal_arithmetic() {
+        let center = Point::from(1, 2);
+        assert_eq!(center + Action::UPLEFT, Point::from(0, 1));
+        assert_eq!(center + Action::UPRIGHT, Point::from(0, 3));
+        assert_eq!(center + Action::DOWNLEFT, Point::from(2, 1));
+        assert_eq!(center + Action::DOWNRIGHT, Point::from(2, 3));
+        assert!(!(Point::from(0, 2) + Action::UPLEFT).inside());
+        assert!(!(Point::from(2, 0) + Action::DOWNLEFT).inside());
+        assert!(!(Point::from(1, N - 1) + Action::DOWNRIGHT).inside());
+        assert_eq!(
+            Point::from(0, 0).wrapping_add(Action::UPLEFT),
+            Point::from(N - 1, N - 1)
+        );
+        for action in ACTIONS {
+            assert_eq!(center + action + action.opposite(), center);
+            assert_eq!(Action::from_letter(action.letter()), Some(action));
+            for t in Transform::ALL {
+                assert_eq!(
+                    (center + action).symmetry(t),
+                    center.symmetry(t) + action.symmetry(t),
+                    "should move {action} consistently under {t:?}"
+                );
+            }
+        }
+        let seq: ActionSequence = ACTIONS.into_iter().collect();
+        assert_eq!((0..seq.length()).map(|i| seq.get(i)).collect_vec(), ACTIONS);
+        assert_eq!(ActionSequence::MAX_LENGTH, 19);
+    }
 }
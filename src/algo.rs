@@ -0,0 +1,311 @@
+use crate::action::{Action, ACTIONS};
+use crate::board::Board;
+use crate::solve::{
+    reverse_rotate_solution, rotate_solution, solve_board_with, SolveOutcome, SolverConfig,
+};
+use crate::values::Transform;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+/**
+   A search algorithm for shortest solutions, selectable at runtime.
+*/
+pub trait Solve {
+    fn solve(&self, board: &Board, max_moves: usize) -> Option<Vec<Action>>;
+
+    /**
+       Like `solve`, but telling why no solution was found if the algorithm knows.
+    */
+    fn solve_outcome(&self, board: &Board, max_moves: usize) -> SolveOutcome {
+        match self.solve(board, max_moves) {
+            Some(actions) => SolveOutcome::Solved(actions),
+            None => SolveOutcome::Unsolvable,
+        }
+    }
+
+    /**
+       Whether the solutions of the symmetric variants of a board are the symmetric
+       variants of its solutions.
+    */
+    fn is_symmetric(&self) -> bool {
+        true
+    }
+}
+
+/**
+   Breadth-first search of `solve_board_with`.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct Bfs(pub SolverConfig);
+
+impl Solve for Bfs {
+    fn solve(&self, board: &Board, max_moves: usize) -> Option<Vec<Action>> {
+        self.solve_outcome(board, max_moves).solution()
+    }
+
+    fn solve_outcome(&self, board: &Board, max_moves: usize) -> SolveOutcome {
+        solve_board_with(board, max_moves, &self.0)
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.0.is_symmetric()
+    }
+}
+
+/**
+   Iterative deepening depth-first search pruned by `Board::heuristic`.
+   It remembers the largest budget for which a board failed across iterations, such that
+   boards reached again with no more moves left are skipped.
+   Moves returning to a board on the current path are skipped as well, since such a
+   cycle, e.g. moving the cursor back and forth over zero cells, never shortens a solution.
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Iddfs;
+
+struct IddfsState {
+    path: Vec<Action>,
+    on_path: HashSet<Board>,
+    failed: HashMap<Board, usize>,
+}
+
+impl Iddfs {
+    fn search(board: &Board, remaining: usize, state: &mut IddfsState) -> bool {
+        if board.is_won() {
+            return true;
+        }
+        if board.heuristic() > remaining || state.failed.get(board).is_some_and(|r| *r >= remaining)
+        {
+            return false;
+        }
+        state.on_path.insert(*board);
+        for action in ACTIONS {
+            let Some(next) = board.action(action) else {
+                continue;
+            };
+            if next.is_lost() || state.on_path.contains(&next) {
+                continue;
+            }
+            state.path.push(action);
+            if Self::search(&next, remaining - 1, state) {
+                return true;
+            }
+            state.path.pop();
+        }
+        state.on_path.remove(board);
+        state.failed.insert(*board, remaining);
+        false
+    }
+}
+
+impl Solve for Iddfs {
+    fn solve(&self, board: &Board, max_moves: usize) -> Option<Vec<Action>> {
+        let mut state = IddfsState {
+            path: Vec::with_capacity(max_moves),
+            on_path: HashSet::new(),
+            failed: HashMap::new(),
+        };
+        (0..=max_moves)
+            .find(|depth| Self::search(board, *depth, &mut state))
+            .map(|_| state.path)
+    }
+}
+
+/**
+   How often `Cached` answered from its cache, either for the same board or
+   for a symmetric variant of it, and how often it had to search.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub symmetric_hits: usize,
+    pub misses: usize,
+}
+
+struct CacheEntry {
+    /// The board which was searched
+    board: Board,
+    /// Shortest solution of the canonical board, if any within `max_moves`
+    solution: Option<Vec<Action>>,
+    max_moves: usize,
+}
+
+impl CacheEntry {
+    /**
+       The known answer for a budget of `max_moves`: a shortest solution is the answer
+       to every budget, but no solution only to budgets as small as the searched one.
+    */
+    fn answer(&self, max_moves: usize) -> Option<Option<&Vec<Action>>> {
+        match &self.solution {
+            Some(actions) => Some((actions.len() <= max_moves).then_some(actions)),
+            None => (max_moves <= self.max_moves).then_some(None),
+        }
+    }
+}
+
+/**
+   Remember the outcomes of `inner` by canonical board, so a board is searched
+   only once together with all its symmetric variants.
+   Only solved and unsolvable outcomes are kept.
+   If `inner` is not symmetric, every board is remembered on its own.
+*/
+pub struct Cached<'a> {
+    inner: &'a dyn Solve,
+    entries: RefCell<HashMap<Board, CacheEntry>>,
+    stats: Cell<CacheStats>,
+}
+
+impl<'a> Cached<'a> {
+    pub fn new(inner: &'a dyn Solve) -> Self {
+        Self {
+            inner,
+            entries: RefCell::new(HashMap::new()),
+            stats: Cell::new(CacheStats::default()),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+}
+
+impl Solve for Cached<'_> {
+    fn solve(&self, board: &Board, max_moves: usize) -> Option<Vec<Action>> {
+        self.solve_outcome(board, max_moves).solution()
+    }
+
+    fn solve_outcome(&self, board: &Board, max_moves: usize) -> SolveOutcome {
+        let (canonical, t) = if self.inner.is_symmetric() {
+            let canonical = board.canonical();
+            let t = Transform::ALL
+                .into_iter()
+                .find(|t| board.symmetry(*t) == canonical)
+                .unwrap();
+            (canonical, t)
+        } else {
+            (*board, Transform::Identity)
+        };
+        let mut stats = self.stats.get();
+        let known = self.entries.borrow().get(&canonical).and_then(|entry| {
+            let answer = entry.answer(max_moves)?;
+            Some((
+                entry.board == *board,
+                answer.map(|a| reverse_rotate_solution(a, t)),
+            ))
+        });
+        if let Some((same, answer)) = known {
+            if same {
+                stats.hits += 1;
+            } else {
+                stats.symmetric_hits += 1;
+            }
+            self.stats.set(stats);
+            return match answer {
+                Some(actions) => SolveOutcome::Solved(actions),
+                None => SolveOutcome::Unsolvable,
+            };
+        }
+        stats.misses += 1;
+        self.stats.set(stats);
+        let outcome = self.inner.solve_outcome(board, max_moves);
+        let solution = match &outcome {
+            SolveOutcome::Solved(actions) => Some(rotate_solution(actions, t)),
+            SolveOutcome::Unsolvable => None,
+            _ => return outcome,
+        };
+        self.entries.borrow_mut().insert(
+            canonical,
+            CacheEntry {
+                board: *board,
+                solution,
+                max_moves,
+            },
+        );
+        outcome
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.inner.is_symmetric()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::{verify_solution, DEFAULT_MOVES};
+    use crate::test_util::SAMPLE_MOVES;
+    use crate::values::Point;
+
+    #[test]
+    fn solve_with_each_algorithm() {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        let algorithms: Vec<Box<dyn Solve>> = vec![Box::new(Bfs::default()), Box::new(Iddfs)];
+        for algorithm in algorithms {
+            let solution = algorithm
+                .solve(&board, DEFAULT_MOVES)
+                .expect("should solve board");
+            assert!(verify_solution(&board, &solution));
+            assert_eq!(solution.len(), SAMPLE_MOVES);
+        }
+    }
+
+    #[test]
+    fn skip_cycles() {
+        let board: Board = "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(Iddfs.solve(&board, 29), None);
+        let moved = board.clone_with_cursor(Point::from(1, 1));
+        assert_eq!(Iddfs.solve(&moved, 29), None);
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        assert_eq!(Iddfs.solve(&board, 29).map(|s| s.len()), Some(4));
+    }
+
+    #[test]
+    fn cache_symmetric_boards() {
+        let bfs = Bfs::default();
+        let cached = Cached::new(&bfs);
+        let board: Board = "0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0".parse().unwrap();
+        let mirror = board.symmetry(Transform::Mirror);
+        let solution = cached.solve(&board, 8).unwrap();
+        let mirrored = cached.solve(&mirror, 8).unwrap();
+        assert_eq!(
+            cached.stats(),
+            CacheStats {
+                hits: 0,
+                symmetric_hits: 1,
+                misses: 1
+            }
+        );
+        assert!(verify_solution(&mirror, &mirrored));
+        assert_eq!(mirrored.len(), solution.len());
+        assert_eq!(cached.solve(&board, 8), Some(solution));
+        assert_eq!(
+            cached.solve(&board, 3),
+            None,
+            "should answer smaller budgets"
+        );
+        assert_eq!(cached.stats().hits, 2);
+
+        let unsolvable: Board = "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(cached.solve(&unsolvable, 4), None);
+        assert_eq!(cached.solve(&unsolvable, 6), None);
+        assert_eq!(
+            cached.stats().misses,
+            3,
+            "should search again with a larger budget"
+        );
+    }
+
+    #[test]
+    fn cache_each_orientation_of_forbidden_actions() {
+        let bfs = Bfs(SolverConfig::default().forbid(&[Action::RIGHT]));
+        let cached = Cached::new(&bfs);
+        let board: Board = "0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0".parse().unwrap();
+        let mirror = board.symmetry(Transform::Mirror);
+        let solution = cached.solve(&board, 8);
+        let mirrored = cached.solve(&mirror, 8);
+        assert_eq!(cached.stats().misses, 2, "should search both boards");
+        assert_eq!(cached.stats().symmetric_hits, 0);
+        assert_eq!(solution, bfs.solve(&board, 8));
+        assert_eq!(mirrored, bfs.solve(&mirror, 8));
+        assert!(mirrored.is_some_and(|m| !m.contains(&Action::RIGHT)));
+    }
+}
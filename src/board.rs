@@ -1,163 +1,999 @@
-use crate::action::Action;
-use crate::values::{CellNumber, Point, N};
+use crate::action::{Action, ACTIONS};
+use crate::marks::MarkBoard;
+use crate::rng::Rng;
+use crate::values::{CellNumber, Point, Transform, N};
 use itertools::Itertools;
 use std::cmp::{max, min};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::fmt;
 use std::str::FromStr;
 
-fn cell_num_diff(num: CellNumber, origin: CellNumber) -> CellNumber {
+/**
+   Value of cell `num` after a move from `origin` sweeps over it.
+   In the game adjacent values add up without a limit, so their sum may not fit
+   into a cell. Such a result is None and the solver treats the move as impossible.
+*/
+fn cell_num_diff(num: CellNumber, origin: CellNumber) -> Option<CellNumber> {
     if num == origin {
-        0
+        Some(0)
     } else {
         let low = min(num, origin);
         let high = max(num, origin);
         if high > low + 1 {
-            high - low
+            Some(high - low)
         } else {
-            high + low
+            high.checked_add(low)
         }
     }
 }
 
+/**
+   All nonzero values which `cell_num_diff` turns into `result` for `origin`.
+*/
+fn cell_num_sources(result: CellNumber, origin: CellNumber) -> Vec<CellNumber> {
+    [
+        origin.checked_add(result),
+        origin.checked_sub(result),
+        origin.checked_add(1),
+        origin.checked_sub(1),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|&v| v > 0 && v != origin)
+    .filter(|&v| cell_num_diff(v, origin) == Some(result))
+    .unique()
+    .collect()
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub struct Board {
     pos: Point,
+    /// Without the `walls` feature no cell is a wall. Walls hold the value 0.
+    walls: MarkBoard,
     cells: u128,
 }
 
 impl Board {
-    fn cell(&self, p: Point) -> CellNumber {
+    pub fn from_grid(grid: [[CellNumber; N]; N], pos: Point) -> Self {
+        let mut board = Self {
+            pos,
+            walls: MarkBoard::new(),
+            cells: 0,
+        };
+        for (r, row) in grid.iter().enumerate() {
+            for (c, v) in row.iter().enumerate() {
+                board.set_cell(Point::from(r, c), *v);
+            }
+        }
+        board
+    }
+
+    /**
+       Board with pseudo-random cells for the seed and the cursor at the origin.
+       It is not necessarily solvable.
+    */
+    pub fn random(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let low = rng.next_u64() as u128;
+        let high = rng.next_u64() as u128;
+        Self {
+            pos: Point::from(0, 0),
+            walls: MarkBoard::new(),
+            cells: high << 64 | low,
+        }
+    }
+
+    pub fn to_grid(&self) -> [[CellNumber; N]; N] {
+        let mut grid = [[0; N]; N];
+        for (r, row) in grid.iter_mut().enumerate() {
+            for (c, v) in row.iter_mut().enumerate() {
+                *v = self.cell(Point::from(r, c));
+            }
+        }
+        grid
+    }
+
+    pub fn cursor(&self) -> Point {
+        self.pos
+    }
+
+    /**
+       A board is valid if a game can go on from it: the cursor must be on the grid
+       and not on a wall, and the board must not be lost already.
+       Rule variants add their own constraints here.
+    */
+    pub fn is_valid(&self) -> bool {
+        self.pos.inside() && !self.is_wall(self.pos) && !self.is_lost()
+    }
+
+    pub fn cell(&self, p: Point) -> CellNumber {
         (self.cells >> (p.index() * 8)) as u8
     }
 
+    /**
+       Whether the cell at `p` is a wall, which the cursor cannot enter and moves cannot sweep.
+       Walls are kept apart from the cell values: a wall cell holds 0, so it never needs
+       clearing and never counts as dead. Always false without the `walls` feature.
+    */
+    pub fn is_wall(&self, p: Point) -> bool {
+        cfg!(feature = "walls") && self.walls.is_marked(p)
+    }
+
+    /**
+       The wall cells of the board.
+    */
+    pub fn walls(&self) -> MarkBoard {
+        self.walls
+    }
+
+    /**
+       Copy of the board with a wall at `p` instead of its cell, which must not hold the cursor.
+    */
+    #[cfg(feature = "walls")]
+    pub fn with_wall(&self, p: Point) -> Self {
+        assert!(p != self.pos, "wall should not be on the cursor");
+        let mut board = self.with_cell(p, 0);
+        board.walls.mark(p);
+        board
+    }
+
     fn set_cell(&mut self, p: Point, v: CellNumber) {
         self.cells ^= ((self.cell(p) ^ v) as u128) << (p.index() * 8)
     }
 
-    fn apply_action(&mut self, p: Point, action: Action) -> u8 {
+    /**
+       Copy of the board with the cell at `p` set to `v`.
+    */
+    pub fn with_cell(&self, p: Point, v: CellNumber) -> Self {
+        let mut board = *self;
+        board.set_cell(p, v);
+        board
+    }
+
+    /**
+       Copy of the board with the cursor moved to `p`, which must be on the grid.
+    */
+    pub fn clone_with_cursor(&self, p: Point) -> Self {
+        assert!(p.inside(), "cursor should be on the grid");
+        Self { pos: p, ..*self }
+    }
+
+    fn apply_action(&mut self, p: Point, action: Action, wrap: bool) -> Option<u8> {
         let mut clears: u8 = 0;
         let origin = self.cell(p);
-        if origin > 0 {
-            let mut pos = p + action;
-            while pos.inside() {
+        if origin > 0 && !self.is_wall(p) {
+            let step = |pos: Point| {
+                if wrap {
+                    pos.wrapping_add(action)
+                } else {
+                    pos + action
+                }
+            };
+            let mut pos = step(p);
+            while pos.inside() && pos != p && !self.is_wall(pos) {
                 let v = self.cell(pos);
                 if v > 0 {
-                    let nv = cell_num_diff(v, origin);
+                    let nv = cell_num_diff(v, origin)?;
                     clears += (nv == 0) as u8;
                     self.set_cell(pos, nv);
                 }
-                pos = pos + action;
+                pos = step(pos);
             }
             if clears > 0 {
                 self.set_cell(p, 0);
                 clears += 1;
             }
         }
-        clears
+        Some(clears)
+    }
+
+    /**
+       The points swept by `action` in order, from the cell the cursor moves to up to the edge
+       or the first wall, whatever their values. Empty if the cursor would leave the grid.
+    */
+    pub fn action_path(&self, action: Action) -> Vec<Point> {
+        std::iter::successors(Some(self.pos + action), |p| Some(*p + action))
+            .take_while(|p| p.inside() && !self.is_wall(*p))
+            .collect()
     }
 
     pub fn action(&self, action: Action) -> Option<Self> {
+        self.action_with_clears(action).map(|(board, _)| board)
+    }
+
+    /**
+       Like `action`, but also count the cells cleared by the move.
+       None if the cursor would leave the grid or a cell would exceed the maximum value.
+    */
+    pub fn action_with_clears(&self, action: Action) -> Option<(Self, u8)> {
         let pos = self.pos + action;
         if pos.inside() {
-            let mut next_board = Self {
-                pos,
-                cells: self.cells,
-            };
-            next_board.apply_action(self.pos, action);
-            Some(next_board)
+            self.moved(pos, action, false)
         } else {
             None
         }
     }
 
+    /**
+       Move on a toroidal board: the cursor leaving one edge enters on the opposite
+       edge, and the move sweeps all other cells of its line in the direction of the
+       move. Without walls a cell alone in its row and column still can never be cleared,
+       so `is_lost` keeps its meaning. With walls a wrapping move may clear such a cell.
+    */
+    pub fn wrapping_action(&self, action: Action) -> Option<Self> {
+        self.moved(self.pos.wrapping_add(action), action, true)
+            .map(|(board, _)| board)
+    }
+
+    fn moved(&self, pos: Point, action: Action, wrap: bool) -> Option<(Self, u8)> {
+        if self.is_wall(pos) {
+            return None;
+        }
+        let mut next_board = Self { pos, ..*self };
+        let clears = next_board.apply_action(self.pos, action, wrap)?;
+        Some((next_board, clears))
+    }
+
+    /**
+       The move clearing the most cells right now and its clear count.
+       Ties are broken by `ACTIONS` order. None if no move clears any cell.
+    */
+    pub fn best_single_move(&self) -> Option<(Action, u8)> {
+        ACTIONS
+            .into_iter()
+            .filter_map(|a| self.action_with_clears(a).map(|(_, clears)| (a, clears)))
+            .filter(|(_, clears)| *clears > 0)
+            .rev()
+            .max_by_key(|(_, clears)| *clears)
+    }
+
+    /**
+       The cells which differ from `other` as `(point, own value, other value)`
+       by increasing cell index. The cursors are not compared.
+    */
+    pub fn diff(&self, other: &Board) -> Vec<(Point, CellNumber, CellNumber)> {
+        (0..N)
+            .flat_map(Point::row_points)
+            .map(|p| (p, self.cell(p), other.cell(p)))
+            .filter(|(_, a, b)| a != b)
+            .collect()
+    }
+
+    /**
+       How many cells hold each value, including zero.
+    */
+    pub fn value_counts(&self) -> BTreeMap<CellNumber, u8> {
+        let mut counts = BTreeMap::new();
+        for p in (0..N).flat_map(Point::row_points) {
+            *counts.entry(self.cell(p)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /**
+       The boards after every action which stays on the grid, except lost ones.
+    */
+    pub fn children(&self) -> impl Iterator<Item = Board> + '_ {
+        ACTIONS
+            .into_iter()
+            .filter_map(|action| self.action(action))
+            .filter(|b| !b.is_lost())
+    }
+
+    /**
+       Every move with the board after it and its clear count, in `ACTIONS` order and
+       including lost boards. Every move changes the cursor, so the boards are all distinct.
+    */
+    pub fn successors(&self) -> Vec<(Action, Board, u8)> {
+        ACTIONS
+            .into_iter()
+            .filter_map(|a| {
+                self.action_with_clears(a)
+                    .map(|(board, clears)| (a, board, clears))
+            })
+            .collect()
+    }
+
+    /**
+       The average and maximum number of children of the boards in the game tree
+       within `depth` moves, pruned like `children`. Won boards are not expanded.
+    */
+    pub fn branching_stats(&self, depth: usize) -> (f64, usize) {
+        let mut expanded = 0;
+        let mut children = 0;
+        let mut max_children = 0;
+        let mut layer = vec![*self];
+        for _ in 0..depth {
+            let mut next_layer = vec![];
+            for board in layer.iter().filter(|b| !b.is_won()) {
+                let before = next_layer.len();
+                next_layer.extend(board.children());
+                let count = next_layer.len() - before;
+                expanded += 1;
+                children += count;
+                max_children = max(max_children, count);
+            }
+            layer = next_layer;
+        }
+        if expanded == 0 {
+            (0.0, 0)
+        } else {
+            (children as f64 / expanded as f64, max_children)
+        }
+    }
+
+    /**
+       The actions after which the board is lost, e.g. to warn before playing them.
+    */
+    pub fn moves_causing_loss(&self) -> Vec<Action> {
+        ACTIONS
+            .into_iter()
+            .filter(|a| self.action(*a).is_some_and(|b| b.is_lost()))
+            .collect()
+    }
+
+    /**
+       The actions leading to a board which is no symmetric variant of any `seen` board,
+       e.g. to avoid going in circles.
+    */
+    pub fn novel_moves(&self, seen: &HashSet<Board>) -> Vec<Action> {
+        let seen: HashSet<Board> = seen.iter().map(Board::canonical).collect();
+        ACTIONS
+            .into_iter()
+            .filter(|a| {
+                self.action(*a)
+                    .is_some_and(|b| !seen.contains(&b.canonical()))
+            })
+            .collect()
+    }
+
+    /**
+       All `(action, prior)` with `prior.action(action) == Some(*self)`.
+
+       The prior cursor is one step against `action`, at the origin of the move, which
+       cannot be a wall. The move swept the line from the current cursor to the edge or
+       the first wall:
+       - origin 0: nothing changed, so the origin must still be 0.
+       - origin `o` without clears: the origin still holds `o`, zero line cells were 0
+         and every other line cell was one of the values `cell_num_diff` maps to it.
+       - origin `o` with clears: the origin is now 0 and `o` may be any value.
+         Zero line cells were either 0 or `o` (a clear), at least one of them `o`.
+    */
+    pub fn predecessors(&self) -> Vec<(Action, Self)> {
+        let mut result = Vec::new();
+        for action in ACTIONS {
+            let origin = self.pos + action.opposite();
+            if !origin.inside() || self.is_wall(origin) {
+                continue;
+            }
+            let mut line = vec![];
+            let mut p = self.pos;
+            while p.inside() && !self.is_wall(p) {
+                line.push(p);
+                p = p + action;
+            }
+            let moved = Self {
+                pos: origin,
+                ..*self
+            };
+            let with_line = |o: CellNumber, values: Vec<CellNumber>| {
+                let mut prior = moved.with_cell(origin, o);
+                for (p, v) in line.iter().zip(values) {
+                    prior.set_cell(*p, v);
+                }
+                prior
+            };
+            let current = self.cell(origin);
+            let mut priors = vec![];
+            if current == 0 {
+                priors.push(moved);
+                for o in 1..=CellNumber::MAX {
+                    let sources = line.iter().map(|p| match self.cell(*p) {
+                        0 => vec![0, o],
+                        r => cell_num_sources(r, o),
+                    });
+                    priors.extend(
+                        sources
+                            .multi_cartesian_product()
+                            .filter(|values| values.contains(&o))
+                            .map(|values| with_line(o, values)),
+                    );
+                }
+            } else {
+                let sources = line.iter().map(|p| match self.cell(*p) {
+                    0 => vec![0],
+                    r => cell_num_sources(r, current),
+                });
+                priors.extend(
+                    sources
+                        .multi_cartesian_product()
+                        .map(|values| with_line(current, values)),
+                );
+            }
+            for prior in priors {
+                debug_assert_eq!(prior.action(action), Some(*self));
+                result.push((action, prior));
+            }
+        }
+        result
+    }
+
+    /**
+       Apply `actions` in order, or give the index of the first action which is
+       impossible (see `action_with_clears`) together with the board before it.
+    */
+    pub fn apply_actions_checked(&self, actions: &[Action]) -> Result<Self, (usize, Self)> {
+        actions
+            .iter()
+            .enumerate()
+            .try_fold(*self, |board, (i, a)| board.action(*a).ok_or((i, board)))
+    }
+
+    /**
+       Repeatedly apply the action chosen by `policy` until the board is won or lost,
+       the policy gives up or picks an action leaving the grid, or `max` actions are applied.
+       Gives the final board and the number of applied actions.
+    */
+    pub fn rollout(&self, policy: impl Fn(&Board) -> Option<Action>, max: usize) -> (Self, usize) {
+        let mut board = *self;
+        for steps in 0..max {
+            if board.is_won() || board.is_lost() {
+                return (board, steps);
+            }
+            match policy(&board).and_then(|a| board.action(a)) {
+                Some(next) => board = next,
+                None => return (board, steps),
+            }
+        }
+        (board, max)
+    }
+
     fn row(&self, row: usize) -> u32 {
         (self.cells >> (row * N * 8)) as u32
     }
 
     fn col(&self, col: usize) -> u32 {
-        (0..N)
-            .into_iter()
-            .map(|r| (self.cell(Point::from(r, col)) as u32) << (r * 8))
+        Point::col_points(col)
+            .map(|p| (self.cell(p) as u32) << (p.row() * 8))
             .reduce(|acc, e| acc | e)
             .unwrap()
     }
 
     /**
-       A cell is dead if both its the last in its column and row.
+       A cell is dead if both its the last in its column and row (and diagonals).
     */
     fn dead_cell(&self, p: Point) -> bool {
-        let r = p.row();
-        let c = p.column();
-        self.cell(p) != 0
-            && // Row is dead
-                (self.row(r) & !(0xFF << (c * 8))) == 0
-            && // Column is dead
-                (self.col(c) & !(0xFF << (r * 8))) == 0
+        self.cell(p) != 0 && self.last_in_row(p) && self.last_in_column(p) && self.dead_diagonals(p)
+    }
+
+    /**
+       Whether the row of `p` has no other nonzero cell up to the edges or walls.
+    */
+    fn last_in_row(&self, p: Point) -> bool {
+        if cfg!(feature = "walls") {
+            return self.empty_rays(p, [Action::LEFT, Action::RIGHT]);
+        }
+        (self.row(p.row()) & !(0xFF << (p.column() * 8))) == 0
+    }
+
+    /**
+       Whether the column of `p` has no other nonzero cell up to the edges or walls.
+    */
+    fn last_in_column(&self, p: Point) -> bool {
+        if cfg!(feature = "walls") {
+            return self.empty_rays(p, [Action::UP, Action::DOWN]);
+        }
+        (self.col(p.column()) & !(0xFF << (p.row() * 8))) == 0
+    }
+
+    /**
+       Whether all cells from `p` towards the `directions` are zero, up to the edge or a wall.
+    */
+    fn empty_rays(&self, p: Point, directions: impl IntoIterator<Item = Action>) -> bool {
+        directions.into_iter().all(|a| {
+            std::iter::successors(Some(p + a), |q| Some(*q + a))
+                .take_while(|q| q.inside() && !self.is_wall(*q))
+                .all(|q| self.cell(q) == 0)
+        })
+    }
+
+    /**
+       Which lines through the nonzero cell `p` have no other nonzero cell.
+    */
+    fn lonely_lines(&self, p: Point) -> Option<DeadReason> {
+        if self.cell(p) == 0 {
+            return None;
+        }
+        match (self.last_in_row(p), self.last_in_column(p)) {
+            (true, true) => Some(DeadReason::RowAndColumn),
+            (true, false) => Some(DeadReason::Row),
+            (false, true) => Some(DeadReason::Column),
+            (false, false) => None,
+        }
+    }
+
+    #[cfg(not(feature = "diagonals"))]
+    fn dead_diagonals(&self, _p: Point) -> bool {
+        true
     }
 
     /**
-       The board is lost if it contains any dead cell.
+       Whether both diagonal lines through `p` have no other nonzero cell.
+    */
+    #[cfg(feature = "diagonals")]
+    fn dead_diagonals(&self, p: Point) -> bool {
+        self.empty_rays(
+            p,
+            [
+                Action::UPLEFT,
+                Action::UPRIGHT,
+                Action::DOWNLEFT,
+                Action::DOWNRIGHT,
+            ],
+        )
+    }
+
+    /**
+       The board is lost if it contains any dead cell, or if the cursor is trapped
+       before the board is won.
     */
     pub fn is_lost(&self) -> bool {
-        (0..N)
+        (cfg!(feature = "walls") && self.is_cursor_trapped() && !self.is_won())
+            || (0..N)
+                .flat_map(Point::row_points)
+                .any(|p| self.dead_cell(p))
+    }
+
+    /**
+       Whether no move is possible, since every neighbor of the cursor is outside or a wall.
+       Only walls can trap the cursor.
+    */
+    pub fn is_cursor_trapped(&self) -> bool {
+        ACTIONS
             .into_iter()
-            .flat_map(|r| (0..N).into_iter().map(move |c| Point::from(r, c)))
-            .any(|p| self.dead_cell(p))
+            .map(|a| self.pos + a)
+            .all(|p| !p.inside() || self.is_wall(p))
+    }
+
+    /**
+       Why the cell at `p` is dead, or None if it can still be cleared.
+    */
+    pub fn dead_cell_reason(&self, p: Point) -> Option<DeadReason> {
+        self.dead_cell(p).then_some(DeadReason::RowAndColumn)
+    }
+
+    /**
+       The nonzero cells which are the last in their row or in their column, but not yet dead.
+       They become dead once the other cells of their remaining lines are cleared.
+       Lines end at walls, and with diagonal moves a cell alone in both its row and column
+       is at risk as long as a diagonal still has another nonzero cell.
+    */
+    pub fn at_risk_cells(&self) -> Vec<(Point, DeadReason)> {
+        (0..N)
+            .flat_map(Point::row_points)
+            .filter(|p| !self.dead_cell(*p))
+            .filter_map(|p| self.lonely_lines(p).map(|reason| (p, reason)))
+            .collect()
     }
 
     pub fn is_won(&self) -> bool {
-        // Board is won if all cells are 0
+        // Board is won if all cells are 0, walls hold 0 too
         self.cells == 0
     }
+
+    /**
+       Progress towards the win: the number of zero cells which are no walls.
+    */
+    pub fn score(&self) -> usize {
+        (0..N)
+            .flat_map(Point::row_points)
+            .filter(|p| self.cell(*p) == 0 && !self.is_wall(*p))
+            .count()
+    }
+
+    /**
+       The sum of all cell values. It bounds no number of moves, since a move onto
+       a neighbouring value adds both values up and so may increase the sum.
+    */
+    pub fn total_sum(&self) -> u32 {
+        (0..N)
+            .flat_map(Point::row_points)
+            .map(|p| self.cell(p) as u32)
+            .sum()
+    }
+
+    /**
+       Whether all `cells` are zero, e.g. to check a stage of a level.
+    */
+    pub fn cells_cleared(&self, cells: &[Point]) -> bool {
+        cells.iter().all(|p| self.cell(*p) == 0)
+    }
+
+    /**
+       Whether the cells equal those of `target`, wherever the cursors are.
+    */
+    pub fn same_cells(&self, target: &Board) -> bool {
+        self.cells == target.cells
+    }
+
+    /**
+       Whether the cells of `target` can no longer be reached since zero cells stay zero
+       and dead cells never change. For an all-zero target this is `is_lost`.
+    */
+    pub fn misses_target(&self, target: &Board) -> bool {
+        (0..N).flat_map(Point::row_points).any(|p| {
+            let (v, t) = (self.cell(p), target.cell(p));
+            v != t && (v == 0 || self.dead_cell(p))
+        })
+    }
+
+    /**
+       Lower bound for the number of moves to win: the fewest rows and columns
+       which together cover all nonzero cells.
+
+       Proof sketch: A zero cell never becomes nonzero and a nonzero cell only becomes
+       zero when it is cleared. A move clears cells of a single row or column only
+       (the origin and the swept line). Hence, the clearing moves alone must cover all
+       nonzero cells with lines, which takes at least this many moves.
+    */
+    #[cfg(not(feature = "diagonals"))]
+    pub fn heuristic(&self) -> usize {
+        let nonzero: Vec<Point> = (0..N * N)
+            .map(|i| Point::from(i / N, i % N))
+            .filter(|p| self.cell(*p) != 0)
+            .collect();
+        // Bits 0..N select rows and bits N..2N select columns
+        (0..1u32 << (2 * N))
+            .filter(|lines| {
+                nonzero
+                    .iter()
+                    .all(|p| lines & (1 << p.row() | 1 << (N + p.column())) != 0)
+            })
+            .map(|lines| lines.count_ones() as usize)
+            .min()
+            .unwrap()
+    }
+
+    /**
+       Lower bound for the number of moves to win with diagonal moves: a move clears
+       at most the `N` cells of a single line, so it takes at least a move per `N`
+       nonzero cells.
+    */
+    #[cfg(feature = "diagonals")]
+    pub fn heuristic(&self) -> usize {
+        (0..N)
+            .flat_map(Point::row_points)
+            .filter(|p| self.cell(*p) != 0)
+            .count()
+            .div_ceil(N)
+    }
+
+    /**
+       The connected regions of zero cells ordered by their lowest cell index.
+       The cursor moves freely inside a region without changing any cell.
+    */
+    pub fn zero_regions(&self) -> Vec<MarkBoard> {
+        let mut seen = MarkBoard::new();
+        let mut regions = vec![];
+        for start in (0..N * N).map(|i| Point::from(i / N, i % N)) {
+            if self.cell(start) != 0 || self.is_wall(start) || seen.is_marked(start) {
+                continue;
+            }
+            let mut region = MarkBoard::new();
+            let mut todo = vec![start];
+            seen.mark(start);
+            while let Some(p) = todo.pop() {
+                region.mark(p);
+                for q in ACTIONS.map(|a| p + a) {
+                    if q.inside() && self.cell(q) == 0 && !self.is_wall(q) && !seen.is_marked(q) {
+                        seen.mark(q);
+                        todo.push(q);
+                    }
+                }
+            }
+            regions.push(region);
+        }
+        regions
+    }
+
+    /**
+       The number of cells in the zero region of the cursor, or 0 on a nonzero cell.
+       The larger the region, the more freely the cursor repositions.
+    */
+    pub fn cursor_region_size(&self) -> usize {
+        self.zero_regions()
+            .into_iter()
+            .find(|region| region.is_marked(self.pos))
+            .map_or(0, |region| region.count() as usize)
+    }
+
+    /**
+       Walk the cursor to `end` over zero cells with the fewest moves, giving the board
+       with the cursor on `end` and the moves. Only the last move may enter a nonzero cell,
+       so no cell changes on the way. None if `end` cannot be reached like this.
+    */
+    pub fn walk_to(&self, end: Point) -> Option<(Self, Vec<Action>)> {
+        let mut seen = MarkBoard::new();
+        seen.mark(self.pos);
+        let mut todo = VecDeque::from([(*self, vec![])]);
+        while let Some((board, actions)) = todo.pop_front() {
+            if board.pos == end {
+                return Some((board, actions));
+            }
+            if board.cell(board.pos) != 0 {
+                continue;
+            }
+            for a in ACTIONS {
+                let Some(next) = board.action(a) else {
+                    continue;
+                };
+                if !seen.is_marked(next.pos) {
+                    debug_assert!(next.same_cells(self), "should walk over zero cells");
+                    seen.mark(next.pos);
+                    let mut walk = actions.clone();
+                    walk.push(a);
+                    todo.push_back((next, walk));
+                }
+            }
+        }
+        None
+    }
+
+    /**
+       Move a cursor on a zero cell to the lowest cell index of its zero region.
+
+       Both boards reach the same positions, since moving within a zero region never
+       changes a cell. However, such moves still count, so the normalized board may be
+       closer to or further from the win. The solver therefore does not apply this.
+    */
+    pub fn normalize(&self) -> Self {
+        let pos = self
+            .zero_regions()
+            .into_iter()
+            .find(|region| region.is_marked(self.pos))
+            .and_then(|region| region.first())
+            .unwrap_or(self.pos);
+        Self { pos, ..*self }
+    }
+
+    /**
+       Swap rows and columns of the cells and the cursor.
+    */
+    pub fn transpose(&self) -> Self {
+        self.symmetry(Transform::MirrorDeg270)
+    }
+
+    /**
+       The board with its cells, walls and cursor transformed by `t`.
+    */
+    pub fn symmetry(&self, t: Transform) -> Self {
+        let mut board = Self {
+            pos: self.pos.symmetry(t),
+            walls: self.walls.symmetry(t),
+            cells: 0,
+        };
+        for i in 0..N * N {
+            let p = Point::from(i / N, i % N);
+            board.set_cell(p.symmetry(t), self.cell(p));
+        }
+        board
+    }
+
+    pub fn reverse_symmetry(&self, t: Transform) -> Self {
+        self.symmetry(t.reverse())
+    }
+
+    /**
+       Representative of all symmetric variants of the board: the variant with the
+       lowest cells, then the lowest cursor index and then the lowest walls.
+    */
+    pub fn canonical(&self) -> Self {
+        let (cells, pos, walls) = self.canonical_key();
+        let pos = pos as usize;
+        Self {
+            pos: Point::from(pos / N, pos % N),
+            walls: MarkBoard::from_bits(walls),
+            cells,
+        }
+    }
+
+    /**
+       The cells, cursor index and walls of `canonical` without building the transformed boards.
+       Boards have equal keys exactly if they are symmetric variants of each other,
+       so the key can replace `canonical` in sets of visited boards.
+       A single `u128` cannot hold it, since the cells already take all of its bits.
+    */
+    pub fn canonical_key(&self) -> CanonicalKey {
+        Transform::ALL
+            .into_iter()
+            .map(|t| {
+                let cells = (0..N)
+                    .flat_map(Point::row_points)
+                    .map(|p| (self.cell(p) as u128) << (p.symmetry(t).index() * 8))
+                    .fold(0, |acc, c| acc | c);
+                (
+                    cells,
+                    self.pos.symmetry(t).index() as u8,
+                    self.walls.symmetry(t).bits(),
+                )
+            })
+            .min()
+            .unwrap()
+    }
+
+    /**
+       Whether `other` is one of the 8 symmetric variants of the board,
+       with cells and cursor transformed alike.
+    */
+    pub fn symmetric_eq(&self, other: &Board) -> bool {
+        Transform::ALL
+            .into_iter()
+            .any(|t| self.symmetry(t) == *other)
+    }
+
+    /**
+       Compact form which stays the same across runs and platforms: the cells as
+       little endian bytes, the index of the cursor and the walls as little endian bits.
+    */
+    pub fn to_bytes(&self) -> [u8; N * N + 3] {
+        let mut bytes = [0; N * N + 3];
+        bytes[..N * N].copy_from_slice(&self.cells.to_le_bytes());
+        bytes[N * N] = self.pos.index() as u8;
+        bytes[N * N + 1..].copy_from_slice(&self.walls.bits().to_le_bytes());
+        bytes
+    }
+
+    /**
+       The board written by `to_bytes`, or None if the bytes are no such board:
+       the cursor is off the grid or on a wall, or a wall is nonzero or given without
+       the `walls` feature.
+    */
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; N * N + 3] = bytes.try_into().ok()?;
+        let pos = bytes[N * N] as usize;
+        let board = Self {
+            pos: Point::from(pos / N, pos % N),
+            walls: MarkBoard::from_bits(u16::from_le_bytes([bytes[N * N + 1], bytes[N * N + 2]])),
+            cells: u128::from_le_bytes(bytes[..N * N].try_into().ok()?),
+        };
+        let walls_allowed = cfg!(feature = "walls") || board.walls == MarkBoard::new();
+        let walls_zero = board.walls.iter_marked().all(|p| board.cell(p) == 0);
+        let cursor_free = board.pos.inside() && !board.walls.is_marked(board.pos);
+        (cursor_free && walls_allowed && walls_zero).then_some(board)
+    }
 }
 
+/**
+   Cells, cursor index and wall bits of the canonical board (see `Board::canonical_key`).
+*/
+pub type CanonicalKey = (u128, u8, u16);
+
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         #![allow(unstable_name_collisions)]
         (0..N)
+            .map(|r| {
+                (0..N)
+                    .map(|c| match Point::from(r, c) {
+                        p if self.is_wall(p) => "W".to_string(),
+                        p => self.cell(p).to_string(),
+                    })
+                    .intersperse(" ".into())
+                    .collect_vec()
+            })
+            .intersperse(vec!["|".into()])
+            .flatten()
+            .map(|s| write!(f, "{}", s))
+            .find(|r| r.is_err())
+            .unwrap_or(Ok(()))
+    }
+}
+
+/**
+   The lines through a nonzero cell without any other nonzero cell.
+   Only a cell alone in both its row and its column is dead.
+*/
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum DeadReason {
+    /// No other nonzero cell in the row
+    Row,
+    /// No other nonzero cell in the column
+    Column,
+    /// No other nonzero cell in the row and the column
+    RowAndColumn,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseBoardError {
+    /// A cell is not a number from 0 to 255
+    InvalidCell,
+    /// The board does not have N*N cells
+    WrongCellCount,
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBoardError::InvalidCell => write!(f, "cell is not a number from 0 to 255"),
+            ParseBoardError::WrongCellCount => write!(f, "board does not have {N}x{N} cells"),
+        }
+    }
+}
+
+impl Board {
+    /**
+       Like `from_str`, but pads every row with fewer than `N` cells and a board with fewer
+       than `N` rows with zeros at their end, as some level editors omit the trailing zero cells.
+       Extra rows or cells in a row are rejected as by `from_str`.
+    */
+    pub fn from_str_lenient(s: &str) -> Result<Self, ParseBoardError> {
+        let mut rows = parse_rows(s)?;
+        rows.resize(N, vec![]);
+        let cells: Vec<_> = rows
             .into_iter()
-            .map(|r| {
-                (0..N)
-                    .into_iter()
-                    .map(|c| self.cell(Point::from(r, c)).to_string())
-                    .intersperse(" ".into())
-                    .collect_vec()
+            .flat_map(|mut row| {
+                row.resize(N, Some(0));
+                row
             })
-            .intersperse(vec!["|".into()])
-            .flatten()
-            .map(|s| write!(f, "{}", s))
-            .find(|r| r.is_err())
-            .unwrap_or(Ok(()))
+            .collect();
+        Ok(Self::from_cells(&cells))
+    }
+
+    fn from_cells(cells: &[Option<CellNumber>]) -> Self {
+        let mut board = Board {
+            pos: Point::from(0, 0),
+            walls: MarkBoard::new(),
+            cells: 0,
+        };
+        for (i, c) in cells.iter().enumerate() {
+            let p = Point::from(i / N, i % N);
+            match c {
+                Some(v) => board.set_cell(p, *v),
+                None => board.walls.mark(p),
+            }
+        }
+        board
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseBoardError;
+/**
+   The cells of every row, None for a wall. A single `|` wrapping the rows on
+   either side is ignored.
+*/
+fn parse_rows(s: &str) -> Result<Vec<Vec<Option<CellNumber>>>, ParseBoardError> {
+    let s = s.strip_prefix('|').unwrap_or(s);
+    let s = s.strip_suffix('|').unwrap_or(s);
+    s.splitn(N, '|')
+        .map(|r| {
+            r.splitn(N, ' ')
+                .map(|c| match c {
+                    "W" if cfg!(feature = "walls") => Ok(None),
+                    _ => c
+                        .parse::<CellNumber>()
+                        .map(Some)
+                        .map_err(|_| ParseBoardError::InvalidCell),
+                })
+                .try_collect()
+        })
+        .try_collect()
+}
+
+/**
+   The cells in reading order, None for a wall.
+*/
+fn parse_cells(s: &str) -> Result<Vec<Option<CellNumber>>, ParseBoardError> {
+    Ok(parse_rows(s)?.concat())
+}
 
 impl FromStr for Board {
     type Err = ParseBoardError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers: Vec<_> = s
-            .splitn(N, '|')
-            .flat_map(|r| {
-                r.splitn(N, ' ')
-                    .map(|c| c.parse::<CellNumber>().map_err(|_| ParseBoardError))
-            })
-            .try_collect()?;
-        if numbers.len() == N * N {
-            Ok(Board {
-                pos: Point::from(0, 0),
-                cells: numbers
-                    .into_iter()
-                    .map(|c| c as u128)
-                    .enumerate()
-                    .reduce(|(_, acc), (i, c)| (0, acc | (c << (i * 8))))
-                    .unwrap()
-                    .1,
-            })
+        let cells = parse_cells(s)?;
+        if cells.len() == N * N {
+            Ok(Self::from_cells(&cells))
         } else {
-            Err(ParseBoardError)
+            Err(ParseBoardError::WrongCellCount)
         }
     }
 }
@@ -171,27 +1007,137 @@ mod tests {
         let board = board_str.parse::<Board>();
         assert!(board.is_ok(), "should parse valid board");
         assert_eq!(board.unwrap().to_string(), board_str);
-        assert!(
-            "18 9 6 0|0 255 3 0|33 18 18 3|0 0 15"
-                .parse::<Board>()
-                .is_err(),
+        assert_eq!(
+            "18 9 6 0|0 255 3 0|33 18 18 3|0 0 15".parse::<Board>(),
+            Err(ParseBoardError::WrongCellCount),
             "should be 4x4"
         );
-        assert!(
-            "18 9 6 0|0 256 3 0|33 18 18 3|0 0 15 0"
-                .parse::<Board>()
-                .is_err(),
+        assert_eq!(
+            "18 9 6 0|0 256 3 0|33 18 18 3|0 0 15 0".parse::<Board>(),
+            Err(ParseBoardError::InvalidCell),
             "should be max 255"
         );
     }
 
+    #[test]
+    #[cfg(feature = "walls")]
+    fn parse_walls() -> Result<(), ParseBoardError> {
+        let board: Board = "W 3 3 0|0 0 0 0|0 0 0 0|0 0 0 W".parse()?;
+        assert!(board.is_wall(Point::from(0, 0)) && board.is_wall(Point::from(3, 3)));
+        assert!(!board.is_wall(Point::from(0, 1)));
+        assert_eq!(board.to_string(), "W 3 3 0|0 0 0 0|0 0 0 0|0 0 0 W");
+        assert!(!board.is_lost(), "should not count walls as dead cells");
+        let inner = board.clone_with_cursor(Point::from(0, 1));
+        assert_eq!(inner.action(Action::LEFT), None, "should not enter a wall");
+        assert!(inner.action(Action::RIGHT).unwrap().is_won());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn trap_cursor() -> Result<(), ParseBoardError> {
+        let trapped: Board = "0 W 3 3|W W 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert!(trapped.is_cursor_trapped());
+        assert!(trapped.is_lost(), "should be lost without dead cells");
+        assert!(trapped.successors().is_empty());
+        let free = trapped.clone_with_cursor(Point::from(2, 2));
+        assert!(!free.is_cursor_trapped() && !free.is_lost());
+        let won: Board = "0 W 0 0|W W 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert!(won.is_cursor_trapped() && !won.is_lost());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn walls_apart_from_values() -> Result<(), ParseBoardError> {
+        let board: Board = "255 W 6 0|0 9 3 0|W 18 18 3|0 0 15 255".parse()?;
+        assert_eq!(board.cell(Point::from(0, 0)), 255);
+        assert!(!board.is_wall(Point::from(0, 0)), "should keep 255 a value");
+        assert_eq!(board.to_string(), "255 W 6 0|0 9 3 0|W 18 18 3|0 0 15 255");
+        assert_eq!(Board::from_bytes(&board.to_bytes()), Some(board));
+        let mut on_wall = board.to_bytes();
+        on_wall[N * N] = Point::from(0, 1).index() as u8;
+        assert_eq!(
+            Board::from_bytes(&on_wall),
+            None,
+            "should reject cursor on wall"
+        );
+        let next = board.action(Action::DOWN).unwrap();
+        assert_eq!(next.walls(), board.walls(), "should not move the walls");
+        let predecessors = next.predecessors();
+        assert!(predecessors.contains(&(Action::DOWN, board)));
+        for (action, prior) in predecessors {
+            assert_eq!(prior.walls(), board.walls());
+            assert_eq!(prior.action(action), Some(next), "{prior} {action}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_wrapped_rows() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        assert_eq!("|18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0|".parse(), Ok(board));
+        assert_eq!("|18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse(), Ok(board));
+        assert_eq!("18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0|".parse(), Ok(board));
+        assert_eq!(
+            "||18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0||".parse::<Board>(),
+            Err(ParseBoardError::InvalidCell),
+            "should strip a single pipe only"
+        );
+        assert_eq!(
+            "|18 9 6 0|0 9 3 0|33 18 18 3|".parse::<Board>(),
+            Err(ParseBoardError::WrongCellCount)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_lenient() -> Result<(), ParseBoardError> {
+        let sparse = Board::from_str_lenient("18 9 6 0|0 9 3 0|33 18 18 3|0")?;
+        assert_eq!(sparse, "18 9 6 0|0 9 3 0|33 18 18 3|0 0 0 0".parse()?);
+        assert_eq!(
+            Board::from_str_lenient("3 3")?,
+            "3 3 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse()?
+        );
+        let short_row = Board::from_str_lenient("3 3|4")?;
+        assert_eq!(short_row.cell(Point::from(1, 0)), 4, "should pad each row");
+        assert_eq!(short_row, "3 3 0 0|4 0 0 0|0 0 0 0|0 0 0 0".parse()?);
+        let full = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0";
+        assert_eq!(Board::from_str_lenient(full)?, full.parse()?);
+        assert_eq!(
+            Board::from_str_lenient("18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0 7"),
+            Err(ParseBoardError::InvalidCell),
+            "should reject a fifth cell in a row"
+        );
+        assert_eq!(
+            Board::from_str_lenient("18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0|1"),
+            Err(ParseBoardError::InvalidCell),
+            "should reject a fifth row"
+        );
+        assert_eq!(
+            Board::from_str_lenient("18 9 256"),
+            Err(ParseBoardError::InvalidCell)
+        );
+        assert_eq!(
+            "18 9 6 0|0 9 3 0|33 18 18 3|0".parse::<Board>(),
+            Err(ParseBoardError::WrongCellCount),
+            "should keep the strict parse"
+        );
+        Ok(())
+    }
+
     #[test]
     fn check_lost_won() -> Result<(), ParseBoardError> {
         let alive: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
         assert!(!alive.is_lost(), "should not be lost");
         assert!(!alive.is_won(), "should not be won");
 
-        let lost: Board = "18 9 0 0|0 9 0 0|33 18 0 3|0 0 15 0".parse()?;
+        // With diagonal moves, the 18 next to the 15 could still clear it
+        let lost: Board = if cfg!(feature = "diagonals") {
+            "18 9 0 0|0 9 0 0|33 0 0 0|0 0 15 0".parse()?
+        } else {
+            "18 9 0 0|0 9 0 0|33 18 0 3|0 0 15 0".parse()?
+        };
         println!("cells num: 0x{:032X}", lost.cells);
         let dead_point = Point::from(3, 2);
         let row_num = lost.row(dead_point.row());
@@ -246,4 +1192,699 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn apply_actions_checked() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let valid = [Action::DOWN, Action::RIGHT];
+        assert_eq!(
+            board.apply_actions_checked(&valid),
+            Ok(board
+                .action(Action::DOWN)
+                .and_then(|b| b.action(Action::RIGHT))
+                .unwrap())
+        );
+        let before = board
+            .action(Action::DOWN)
+            .and_then(|b| b.action(Action::UP))
+            .unwrap();
+        assert_eq!(
+            board.apply_actions_checked(&[Action::DOWN, Action::UP, Action::UP, Action::DOWN]),
+            Err((2, before)),
+            "should fail at the third action"
+        );
+        assert_eq!(board.apply_actions_checked(&[]), Ok(board));
+        Ok(())
+    }
+
+    #[test]
+    fn validate_board() -> Result<(), ParseBoardError> {
+        let grid = [[18, 9, 6, 0], [0, 9, 3, 0], [33, 18, 18, 3], [0, 0, 15, 0]];
+        let board = Board::from_grid(grid, Point::from(0, 0));
+        assert_eq!(board, "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?);
+        assert!(board.is_valid());
+        assert!(Board::from_grid(grid, Point::from(3, 3)).is_valid());
+        assert!(
+            !Board::from_grid(grid, Point::from(1, N)).is_valid(),
+            "should reject cursor outside of grid"
+        );
+        assert!(!Board::from_grid(grid, Point::from(N, 0)).is_valid());
+        let lost: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 5".parse()?;
+        assert!(!lost.is_valid(), "should reject lost board");
+        assert_eq!(board.to_grid(), grid);
+        Ok(())
+    }
+
+    #[test]
+    fn set_single_cell() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let p = Point::from(2, 1);
+        let changed = board.with_cell(p, 200);
+        assert_eq!(changed.cell(p), 200);
+        assert_eq!(board.cell(p), 18, "should not modify original");
+        assert_eq!(changed.to_string(), "18 9 6 0|0 9 3 0|33 200 18 3|0 0 15 0");
+        assert_eq!(changed.with_cell(p, 18), board);
+        Ok(())
+    }
+
+    #[test]
+    fn find_best_single_move() -> Result<(), ParseBoardError> {
+        let sample: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        #[cfg(not(feature = "diagonals"))]
+        assert_eq!(sample.best_single_move(), None, "should not clear any cell");
+        #[cfg(feature = "diagonals")]
+        assert_eq!(sample.best_single_move(), Some((Action::DOWNRIGHT, 2)));
+        let combo: Board = "3 3 0 3|3 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(combo.best_single_move(), Some((Action::RIGHT, 3)));
+        let (cleared, clears) = combo.action_with_clears(Action::RIGHT).unwrap();
+        assert_eq!(clears, 3);
+        assert_eq!(cleared.to_string(), "0 0 0 0|3 0 0 0|0 0 0 0|0 0 0 0");
+        let tie: Board = "3 3 0 0|3 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(
+            tie.best_single_move(),
+            Some((Action::DOWN, 2)),
+            "should prefer first action of ties"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn greedy_rollout() -> Result<(), ParseBoardError> {
+        let greedy = |b: &Board| b.best_single_move().map(|(a, _)| a);
+        let combo: Board = "3 3 0 3|3 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        let (end, steps) = combo.rollout(greedy, 10);
+        assert_eq!(steps, 1);
+        assert_eq!(end, combo.action(Action::RIGHT).unwrap());
+        assert!(end.is_lost(), "should stop at lost board");
+        assert_eq!(combo.rollout(greedy, 0), (combo, 0));
+        let stuck: Board = "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(
+            stuck.rollout(greedy, 10),
+            (stuck, 0),
+            "should stop without clears"
+        );
+        let (edge, steps) = stuck.rollout(|_| Some(Action::DOWN), 10);
+        assert_eq!(steps, 3, "should stop at the edge");
+        assert_eq!(edge.cursor(), Point::from(3, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn check_cleared_cells() -> Result<(), ParseBoardError> {
+        let board: Board = "3 3 0 0|3 0 0 0|0 0 5 0|0 0 5 0".parse()?;
+        let partial = board.action(Action::DOWN).unwrap();
+        let stage = [Point::from(0, 0), Point::from(1, 0)];
+        assert!(!board.cells_cleared(&stage));
+        assert!(partial.cells_cleared(&stage));
+        assert!(!partial.cells_cleared(&[Point::from(0, 0), Point::from(0, 1)]));
+        assert!(partial.cells_cleared(&[]));
+        Ok(())
+    }
+
+    #[test]
+    fn sweep_path() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let center = board.clone_with_cursor(Point::from(1, 1));
+        assert_eq!(
+            center.action_path(Action::RIGHT),
+            vec![Point::from(1, 2), Point::from(1, 3)]
+        );
+        assert_eq!(center.action_path(Action::UP), vec![Point::from(0, 1)]);
+        assert!(board.action_path(Action::LEFT).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn sweep_path_up_to_wall() -> Result<(), ParseBoardError> {
+        let board: Board = "3 3 W 3|0 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(board.action_path(Action::RIGHT), vec![Point::from(0, 1)]);
+        let swept = board.action(Action::RIGHT).unwrap();
+        assert_eq!(
+            swept.cell(Point::from(0, 3)),
+            3,
+            "should not sweep past the wall"
+        );
+        let next = board.clone_with_cursor(Point::from(0, 1));
+        assert!(next.action_path(Action::RIGHT).is_empty());
+        assert_eq!(next.action(Action::RIGHT), None);
+        Ok(())
+    }
+
+    #[test]
+    fn diff_boards() -> Result<(), ParseBoardError> {
+        let board: Board = "3 3 0 0|3 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        let down = board.action(Action::DOWN).unwrap();
+        assert_eq!(
+            board.diff(&down),
+            vec![(Point::from(0, 0), 3, 0), (Point::from(1, 0), 3, 0)]
+        );
+        assert_eq!(down.diff(&board)[0], (Point::from(0, 0), 0, 3));
+        assert!(board
+            .diff(&board.clone_with_cursor(Point::from(2, 2)))
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn count_values() -> Result<(), ParseBoardError> {
+        let sample: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let counts = sample.value_counts();
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            vec![(0, 6), (3, 2), (6, 1), (9, 2), (15, 1), (18, 3), (33, 1)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sum_cells() -> Result<(), ParseBoardError> {
+        let sample: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        assert_eq!(sample.total_sum(), 132);
+        let full: Board =
+            "255 255 255 255|255 255 255 255|255 255 255 255|255 255 255 255".parse()?;
+        assert_eq!(full.total_sum(), 255 * 16);
+        let adjacent: Board = "2 3 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        let moved = adjacent.action(Action::RIGHT).unwrap();
+        assert!(
+            moved.total_sum() > adjacent.total_sum(),
+            "should add up neighbouring values"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn measure_branching() -> Result<(), ParseBoardError> {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse()?;
+        assert_eq!(board.branching_stats(0), (0.0, 0));
+        let (corner, open) = if cfg!(feature = "diagonals") {
+            (3, 8)
+        } else {
+            (2, 4)
+        };
+        assert_eq!(
+            board.branching_stats(1),
+            (corner as f64, corner),
+            "should be boxed in a corner"
+        );
+        let centered = board.clone_with_cursor(Point::from(1, 1));
+        assert_eq!(centered.branching_stats(1), (open as f64, open));
+        let (average, max_children) = board.branching_stats(4);
+        assert!(
+            average > corner as f64 && average < open as f64,
+            "{average}"
+        );
+        assert_eq!(max_children, open);
+        Ok(())
+    }
+
+    #[test]
+    fn warn_losing_moves() -> Result<(), ParseBoardError> {
+        let board: Board = "3 3 0 3|3 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(board.moves_causing_loss(), vec![Action::RIGHT]);
+        let safe: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        assert!(safe.moves_causing_loss().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "diagonals"))]
+    fn explain_dead_cells() -> Result<(), ParseBoardError> {
+        let board: Board = "0 0 0 5|0 1 0 0|0 2 0 0|0 0 0 0".parse()?;
+        assert_eq!(
+            board.dead_cell_reason(Point::from(0, 3)),
+            Some(DeadReason::RowAndColumn)
+        );
+        assert_eq!(board.dead_cell_reason(Point::from(1, 1)), None);
+        assert_eq!(board.dead_cell_reason(Point::from(3, 3)), None);
+        assert_eq!(
+            board.at_risk_cells(),
+            vec![
+                (Point::from(1, 1), DeadReason::Row),
+                (Point::from(2, 1), DeadReason::Row)
+            ]
+        );
+        let alive: Board = "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0".parse()?;
+        assert!(!alive.is_lost());
+        assert_eq!(
+            alive.at_risk_cells(),
+            vec![
+                (Point::from(1, 1), DeadReason::Column),
+                (Point::from(1, 2), DeadReason::Column)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn dead_cells_behind_walls() -> Result<(), ParseBoardError> {
+        let board: Board = "3 W 0 0|0 0 0 0|W 0 4 0|0 0 0 0".parse()?;
+        let corner = Point::from(0, 0);
+        if cfg!(feature = "diagonals") {
+            // Each of the cells can still clear the other along the diagonal
+            assert!(!board.is_lost());
+            assert_eq!(board.dead_cell_reason(corner), None);
+            assert_eq!(
+                board.at_risk_cells(),
+                vec![
+                    (corner, DeadReason::RowAndColumn),
+                    (Point::from(2, 2), DeadReason::RowAndColumn)
+                ]
+            );
+        } else {
+            assert!(board.is_lost());
+            assert_eq!(
+                board.dead_cell_reason(corner),
+                Some(DeadReason::RowAndColumn)
+            );
+            assert_eq!(board.at_risk_cells(), vec![]);
+        }
+        let open: Board = "3 0 0 5|0 0 0 0|W 0 0 0|0 0 0 0".parse()?;
+        assert!(!open.is_lost(), "should clear the corner along its row");
+        assert!(open.at_risk_cells().contains(&(corner, DeadReason::Column)));
+        Ok(())
+    }
+
+    #[test]
+    fn list_successors() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let down = board.action(Action::DOWN).unwrap();
+        let right = board.action(Action::RIGHT).unwrap();
+        #[cfg(not(feature = "diagonals"))]
+        let expected = vec![(Action::DOWN, down, 0), (Action::RIGHT, right, 0)];
+        #[cfg(feature = "diagonals")]
+        let expected = vec![
+            (Action::DOWN, down, 0),
+            (Action::RIGHT, right, 0),
+            (
+                Action::DOWNRIGHT,
+                board.action(Action::DOWNRIGHT).unwrap(),
+                2,
+            ),
+        ];
+        assert_eq!(board.successors(), expected);
+        let inner = board.clone_with_cursor(Point::from(1, 1));
+        let successors = inner.successors();
+        assert_eq!(successors.len(), ACTIONS.len());
+        for (action, next, clears) in successors {
+            assert_eq!(inner.action_with_clears(action), Some((next, clears)));
+        }
+        let (_, _, clears) = inner.successors()[0];
+        assert_eq!(clears, 2, "should clear both 9s moving up");
+        Ok(())
+    }
+
+    #[test]
+    fn avoid_seen_boards() -> Result<(), ParseBoardError> {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse()?;
+        #[cfg(not(feature = "diagonals"))]
+        let moves = vec![Action::DOWN, Action::RIGHT];
+        #[cfg(feature = "diagonals")]
+        let moves = vec![Action::DOWN, Action::RIGHT, Action::DOWNRIGHT];
+        assert_eq!(board.novel_moves(&HashSet::new()), moves);
+        let seen = HashSet::from([board, board.action(Action::DOWN).unwrap()]);
+        assert_eq!(board.novel_moves(&seen), moves[1..]);
+
+        let symmetric: Board = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse()?;
+        let seen = HashSet::from([symmetric.action(Action::DOWN).unwrap()]);
+        #[cfg(not(feature = "diagonals"))]
+        let unseen: Vec<Action> = vec![];
+        #[cfg(feature = "diagonals")]
+        let unseen = vec![Action::DOWNRIGHT];
+        assert_eq!(
+            symmetric.novel_moves(&seen),
+            unseen,
+            "should treat the mirrored move as seen"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn find_predecessors() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let mut current = board;
+        for a in [Action::DOWN, Action::RIGHT, Action::RIGHT, Action::UP] {
+            let next = current.action(a).unwrap();
+            let predecessors = next.predecessors();
+            assert!(
+                predecessors.contains(&(a, current)),
+                "should find actual predecessor of {next}"
+            );
+            for (action, prior) in predecessors {
+                assert_eq!(prior.action(action), Some(next), "{prior} {action}");
+            }
+            current = next;
+        }
+        for o in 1..=CellNumber::MAX {
+            for v in (1..=CellNumber::MAX).filter(|v| *v != o) {
+                if let Some(r) = cell_num_diff(v, o) {
+                    assert!(cell_num_sources(r, o).contains(&v));
+                }
+            }
+        }
+        let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        // From the right or below (or diagonally): an empty origin or a clear of any value
+        let directions = if cfg!(feature = "diagonals") { 3 } else { 2 };
+        assert_eq!(won.predecessors().len(), directions * 256);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "diagonals"))]
+    fn normalize_cursor() -> Result<(), ParseBoardError> {
+        let grid = [[18, 9, 6, 0], [0, 9, 3, 0], [33, 0, 0, 3], [0, 0, 15, 0]];
+        let board = Board::from_grid(grid, Point::from(3, 1));
+        let regions = board.zero_regions();
+        assert_eq!(regions.len(), 4);
+        assert_eq!(regions[0].first(), Some(Point::from(0, 3)));
+        assert_eq!(regions[1].first(), Some(Point::from(1, 0)));
+        assert!(regions[2].is_marked(Point::from(2, 2)));
+        assert_eq!(regions[3].first(), Some(Point::from(3, 3)));
+        let other = Board::from_grid(grid, Point::from(2, 2));
+        assert_eq!(board.normalize(), other.normalize());
+        assert_eq!(board.normalize().cursor(), Point::from(2, 1));
+        let nonzero = Board::from_grid(grid, Point::from(0, 0));
+        assert_eq!(nonzero.normalize(), nonzero);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "diagonals"))]
+    fn measure_cursor_region() -> Result<(), ParseBoardError> {
+        let grid = [[18, 9, 6, 0], [0, 9, 3, 0], [33, 0, 0, 3], [0, 0, 15, 0]];
+        let size = |r, c| Board::from_grid(grid, Point::from(r, c)).cursor_region_size();
+        assert_eq!(size(3, 1), 4);
+        assert_eq!(size(0, 3), 2);
+        assert_eq!(size(3, 3), 1);
+        assert_eq!(size(0, 0), 0, "should be 0 on a nonzero cell");
+        let empty: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(empty.cursor_region_size(), N * N);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "diagonals"))]
+    fn walk_to_target() -> Result<(), ParseBoardError> {
+        let board: Board = "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0".parse()?;
+        let (walked, actions) = board.walk_to(Point::from(2, 2)).unwrap();
+        assert_eq!(walked, board.clone_with_cursor(Point::from(2, 2)));
+        assert_eq!(actions.len(), 4);
+        assert_eq!(board.apply_actions_checked(&actions), Ok(walked));
+        let (onto, actions) = board.walk_to(Point::from(1, 2)).unwrap();
+        assert_eq!(onto.cursor(), Point::from(1, 2));
+        assert_eq!(actions.len(), 3);
+        assert_eq!(board.walk_to(Point::from(0, 0)), Some((board, vec![])));
+
+        let fenced: Board = "0 1 0 0|1 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(
+            fenced.walk_to(Point::from(0, 1)).map(|(_, a)| a),
+            Some(vec![Action::RIGHT])
+        );
+        assert_eq!(fenced.walk_to(Point::from(2, 2)), None);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "diagonals")]
+    fn explain_dead_cells_diagonally() -> Result<(), ParseBoardError> {
+        let dead: Board = "0 5 0 0|0 0 0 3|0 0 0 0|0 0 0 0".parse()?;
+        assert!(dead.is_lost());
+        assert_eq!(
+            dead.dead_cell_reason(Point::from(0, 1)),
+            Some(DeadReason::RowAndColumn)
+        );
+        assert_eq!(dead.at_risk_cells(), vec![]);
+        // Alone in their rows and columns, but on one diagonal
+        let board: Board = "0 0 0 5|0 0 0 0|0 3 0 0|0 0 0 0".parse()?;
+        assert!(!board.is_lost());
+        assert_eq!(board.dead_cell_reason(Point::from(0, 3)), None);
+        assert_eq!(
+            board.at_risk_cells(),
+            vec![
+                (Point::from(0, 3), DeadReason::RowAndColumn),
+                (Point::from(2, 1), DeadReason::RowAndColumn)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "diagonals")]
+    fn zero_regions_diagonally() -> Result<(), ParseBoardError> {
+        // The zero cells all touch at least diagonally
+        let grid = [[18, 9, 6, 0], [0, 9, 3, 0], [33, 0, 0, 3], [0, 0, 15, 0]];
+        let board = Board::from_grid(grid, Point::from(3, 1));
+        let regions = board.zero_regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].first(), Some(Point::from(0, 3)));
+        assert_eq!(board.cursor_region_size(), 8);
+        let other = Board::from_grid(grid, Point::from(3, 3));
+        assert_eq!(board.normalize(), other.normalize());
+        assert_eq!(board.normalize().cursor(), Point::from(0, 3));
+
+        let walk: Board = "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0".parse()?;
+        let (walked, actions) = walk.walk_to(Point::from(2, 2)).unwrap();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(walk.apply_actions_checked(&actions), Ok(walked));
+        let fenced: Board = "0 1 0 0|1 1 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(
+            fenced.walk_to(Point::from(1, 1)).map(|(_, a)| a),
+            Some(vec![Action::DOWNRIGHT])
+        );
+        assert_eq!(fenced.walk_to(Point::from(2, 2)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn wrap_action() -> Result<(), ParseBoardError> {
+        let grid = [[3, 0, 3, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 5]];
+        let edge = Board::from_grid(grid, Point::from(0, 2));
+        assert_eq!(
+            edge.action(Action::RIGHT),
+            Some(Board::from_grid(grid, Point::from(0, 3))),
+            "should not sweep beyond the edge"
+        );
+        let wrapped = edge.wrapping_action(Action::RIGHT).unwrap();
+        assert_eq!(wrapped.to_string(), "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 5");
+        assert_eq!(wrapped.cursor(), Point::from(0, 3));
+        let corner = Board::from_grid(grid, Point::from(3, 3));
+        assert_eq!(corner.action(Action::DOWN), None);
+        let around = corner.wrapping_action(Action::DOWN).unwrap();
+        assert_eq!(around.cursor(), Point::from(0, 3));
+        assert_eq!(around.to_string(), edge.to_string(), "should not clear");
+        Ok(())
+    }
+
+    #[test]
+    fn random_board() -> Result<(), ParseBoardError> {
+        assert_eq!(Board::random(42), Board::random(42));
+        assert_ne!(Board::random(42), Board::random(43));
+        for seed in 0..100 {
+            let board = Board::random(seed);
+            assert_eq!(board.to_string().parse::<Board>()?, board);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn move_cursor() -> Result<(), ParseBoardError> {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse()?;
+        let moved = board.clone_with_cursor(Point::from(3, 0));
+        assert_eq!(moved.to_grid(), board.to_grid());
+        assert_eq!(moved.cursor(), Point::from(3, 0));
+        assert_eq!(moved.clone_with_cursor(board.cursor()), board);
+        assert_eq!(
+            crate::solve::solve_board(&moved, 10),
+            Some(vec![Action::RIGHT])
+        );
+        assert_eq!(
+            crate::solve::solve_board(&board, 10).map(|s| s.len()),
+            Some(4)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transpose_board() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let moved = Board::from_grid(board.to_grid(), Point::from(1, 3));
+        let transposed = moved.transpose();
+        assert_eq!(
+            transposed.to_string(),
+            "18 0 33 0|9 9 18 0|6 3 18 15|0 0 3 0"
+        );
+        assert_eq!(transposed.pos, Point::from(3, 1));
+        assert_eq!(transposed.transpose(), moved);
+        let mut rng = Rng::new(7);
+        for seed in 0..500 {
+            let mut sparse = Board::random(seed);
+            for _ in 0..rng.below(N * N) {
+                sparse = sparse.with_cell(Point::from(rng.below(N), rng.below(N)), 0);
+            }
+            assert_eq!(
+                sparse.is_lost(),
+                sparse.transpose().is_lost(),
+                "should transpose {sparse}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cell_num_diff_domain() {
+        for a in 0..=CellNumber::MAX {
+            assert_eq!(cell_num_diff(a, a), Some(0));
+            for b in 0..=CellNumber::MAX {
+                let diff = cell_num_diff(a, b);
+                assert_eq!(
+                    diff,
+                    cell_num_diff(b, a),
+                    "should be symmetric for {a}, {b}"
+                );
+                let adjacent = a.abs_diff(b) == 1;
+                assert_eq!(
+                    diff.is_none(),
+                    adjacent && a as u16 + b as u16 > CellNumber::MAX as u16,
+                    "should only reject overflowing sums of {a}, {b}"
+                );
+                assert_eq!(diff == Some(0), a == b, "should only clear equal {a}, {b}");
+                if let Some(r) = diff.filter(|_| a > 0 && a != b) {
+                    assert!(
+                        cell_num_sources(r, b).contains(&a),
+                        "should find source {a}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn overflowing_cell_sum() -> Result<(), ParseBoardError> {
+        assert_eq!(cell_num_diff(127, 128), Some(255));
+        assert_eq!(cell_num_diff(201, 200), None, "should not wrap");
+        assert_eq!(cell_num_diff(255, 254), None);
+        assert_eq!(cell_num_diff(255, 100), Some(155));
+        let board: Board = "200 201 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(board.action(Action::RIGHT), None, "should reject overflow");
+        assert_eq!(
+            board.wrapping_action(Action::LEFT),
+            None,
+            "should reject wrapped overflow"
+        );
+        let fits: Board = "100 101 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse()?;
+        assert_eq!(
+            fits.action(Action::RIGHT).map(|b| b.to_string()),
+            Some("100 201 0 0|0 0 0 0|0 0 0 0|0 0 0 0".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_board() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let canonical = board.canonical();
+        for t in Transform::ALL {
+            assert_eq!(board.symmetry(t).canonical(), canonical);
+        }
+        assert_ne!(board.with_cell(Point::from(0, 0), 1).canonical(), canonical);
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_board_properties() {
+        let mut rng = Rng::new(11);
+        for seed in 0..300 {
+            let mut board = Board::random(seed);
+            // Few distinct values make ties between the transformed boards likely
+            for _ in 0..rng.below(2 * N * N) {
+                let p = Point::from(rng.below(N), rng.below(N));
+                board = board.with_cell(p, rng.below(3) as CellNumber);
+            }
+            let board = board.clone_with_cursor(Point::from(rng.below(N), rng.below(N)));
+            let canonical = board.canonical();
+            assert_eq!(
+                canonical.canonical(),
+                canonical,
+                "should be idempotent for {board}"
+            );
+            assert!(
+                canonical.symmetric_eq(&board),
+                "should be a variant of {board}"
+            );
+            for t in Transform::ALL {
+                assert_eq!(
+                    board.symmetry(t).canonical(),
+                    canonical,
+                    "should be stable under {t:?} for {board} with cursor {:?}",
+                    board.pos
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_board_key() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let mirror = board.symmetry(Transform::Mirror);
+        assert_ne!(mirror, board);
+        assert_eq!(mirror.canonical_key(), board.canonical_key());
+        let canonical = board.canonical();
+        assert_eq!(
+            board.canonical_key(),
+            (canonical.cells, canonical.pos.index() as u8, 0)
+        );
+        let moved = board.clone_with_cursor(Point::from(1, 1));
+        assert_ne!(moved.canonical_key(), board.canonical_key());
+        Ok(())
+    }
+
+    #[test]
+    fn board_bytes() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let board = board.clone_with_cursor(Point::from(2, 1));
+        let bytes = board.to_bytes();
+        assert_eq!(bytes[N * N], 9);
+        assert_eq!(Board::from_bytes(&bytes), Some(board));
+        assert_eq!(Board::from_bytes(&bytes[1..]), None);
+        let mut outside = bytes;
+        outside[N * N] = (N * N) as u8;
+        assert_eq!(Board::from_bytes(&outside), None);
+        Ok(())
+    }
+
+    #[test]
+    fn compare_symmetric_boards() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let rotated = board.symmetry(Transform::Deg90);
+        assert!(board.symmetric_eq(&rotated));
+        assert!(rotated.symmetric_eq(&board));
+        let unrelated: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse()?;
+        assert!(!board.symmetric_eq(&unrelated));
+        assert!(
+            !board.symmetric_eq(&rotated.clone_with_cursor(Point::from(0, 0))),
+            "should transform the cursor with the cells"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transform_board() -> Result<(), ParseBoardError> {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse()?;
+        let rotated = board.symmetry(Transform::Deg90);
+        assert_eq!(rotated.to_string(), "0 33 0 18|0 18 9 9|15 18 3 6|0 3 0 0");
+        assert_eq!(rotated.pos, Point::from(0, 3));
+        for t in Transform::ALL {
+            let transformed = board.symmetry(t);
+            assert_eq!(transformed.reverse_symmetry(t), board);
+            for a in ACTIONS {
+                assert_eq!(
+                    board.action(a).map(|b| b.symmetry(t)),
+                    transformed.action(a.symmetry(t)),
+                    "should commute {a} with {t:?}"
+                );
+            }
+        }
+        Ok(())
+    }
 }
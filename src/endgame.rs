@@ -0,0 +1,135 @@
+use crate::board::Board;
+#[cfg(feature = "walls")]
+use crate::marks::MarkBoard;
+use crate::values::{Point, N};
+use std::collections::HashMap;
+
+/**
+   Retrograde table of the optimal number of moves for all boards which can be won
+   within `depth` moves, keyed by `Board::canonical`.
+
+   It is built backwards from the won boards with `Board::predecessors`. Since every
+   board has hundreds of predecessors, only a depth of one or two is feasible.
+*/
+pub struct EndgameTable {
+    depth: usize,
+    distances: HashMap<Board, usize>,
+}
+
+impl EndgameTable {
+    pub fn build(depth: usize) -> Self {
+        Self::build_around(depth, Board::from_grid([[0; N]; N], Point::from(0, 0)))
+    }
+
+    /**
+       Like `build`, but for the boards with the given `walls`.
+    */
+    #[cfg(feature = "walls")]
+    pub fn build_with_walls(depth: usize, walls: MarkBoard) -> Self {
+        let empty = Board::from_grid([[0; N]; N], Point::from(0, 0));
+        let free = (0..N * N)
+            .map(|i| Point::from(i / N, i % N))
+            .find(|p| !walls.is_marked(*p))
+            .expect("should leave a cell for the cursor");
+        let empty = walls
+            .iter_marked()
+            .fold(empty.clone_with_cursor(free), |b, p| b.with_wall(p));
+        Self::build_around(depth, empty)
+    }
+
+    fn build_around(depth: usize, empty: Board) -> Self {
+        let won = (0..N * N)
+            .map(|i| Point::from(i / N, i % N))
+            .filter(|p| !empty.is_wall(*p))
+            .map(|p| empty.clone_with_cursor(p).canonical());
+        let mut distances: HashMap<Board, usize> = won.map(|b| (b, 0)).collect();
+        let mut layer: Vec<Board> = distances.keys().copied().collect();
+        for distance in 1..=depth {
+            layer = layer
+                .iter()
+                .flat_map(|board| board.predecessors())
+                .map(|(_, prior)| prior.canonical())
+                .filter(|prior| {
+                    let new = !distances.contains_key(prior);
+                    if new {
+                        distances.insert(*prior, distance);
+                    }
+                    new
+                })
+                .collect();
+        }
+        Self { depth, distances }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    /**
+       The optimal number of moves to win `board`, if at most the table's depth.
+    */
+    pub fn lookup(&self, board: &Board) -> Option<usize> {
+        self.distances.get(&board.canonical()).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Board, &usize)> {
+        self.distances.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+    use crate::solve::solve_board;
+    use crate::values::Transform;
+
+    #[test]
+    fn confirm_table_entries() {
+        let table = EndgameTable::build(1);
+        assert_eq!(table.depth(), 1);
+        // The won boards and their canonical predecessors
+        let size = if cfg!(feature = "diagonals") {
+            9693
+        } else {
+            5613
+        };
+        assert_eq!(table.len(), size);
+        for (board, distance) in table.iter() {
+            let solution = solve_board(board, *distance);
+            assert_eq!(solution.map(|s| s.len()), Some(*distance), "{board}");
+        }
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 3 3".parse().unwrap();
+        assert_eq!(
+            table.lookup(&board),
+            None,
+            "should be too far for the table"
+        );
+        let near = Board::from_grid(board.to_grid(), Point::from(3, 2));
+        assert_eq!(solve_board(&near, 1), Some(vec![Action::RIGHT]));
+        for t in Transform::ALL {
+            assert_eq!(table.lookup(&near.symmetry(t)), Some(1));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn confirm_walled_entries() {
+        let walls: Board = "0 0 0 0|0 W 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let table = EndgameTable::build_with_walls(1, walls.walls());
+        assert!(!table.is_empty());
+        for (board, distance) in table.iter() {
+            assert_eq!(board.walls().count(), 1, "should keep the wall of {board}");
+            let solution = solve_board(board, *distance);
+            assert_eq!(solution.map(|s| s.len()), Some(*distance), "{board}");
+        }
+    }
+}
@@ -0,0 +1,12 @@
+pub mod action;
+pub mod algo;
+pub mod board;
+pub mod endgame;
+pub mod marks;
+pub mod replay;
+pub mod rng;
+pub mod session;
+pub mod solve;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod values;
@@ -1,74 +1,1169 @@
-mod action;
-mod board;
-mod solve;
-mod values;
-
-use board::Board;
-use action::ActionSequence;
-use clap::Parser;
-use itertools::join;
-use solve::solve_board;
-use std::io;
+use clap::{Parser, ValueEnum};
+use itertools::{join, Itertools};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zoysii_solver::action::{Action, ActionSequence};
+use zoysii_solver::algo::{Bfs, Cached, Iddfs, Solve};
+use zoysii_solver::board::Board;
+use zoysii_solver::replay::write_replay;
+use zoysii_solver::solve::{
+    collapse_walks, count_shortest_solutions, nth_shortest_solution, SolveOutcome, SolverConfig,
+    Step, DEFAULT_MOVES,
+};
+use zoysii_solver::values::{Point, N};
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Algo {
+    /// Breadth-first search
+    Bfs,
+    /// Iterative deepening depth-first search (ignores --limit-visited, --layer-sizes and Ctrl-C)
+    Iddfs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Action names
+    Text,
+    /// One JSON object per board
+    Json,
+    /// The bits of the `ActionSequence` as hexadecimal number, "X" if unsolvable
+    /// or "?" if the search limit was reached. Bits 0-5 hold the number of moves and
+    /// move i takes bits 6+2i and 7+2i with Up=0, Down=1, Left=2, Right=3. Built with
+    /// diagonal moves, move i takes bits 6+3i to 8+3i with UpLeft=4, UpRight=5,
+    /// DownLeft=6 and DownRight=7 in addition
+    Packed,
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Max number of moves
-    #[arg(short, long, default_value_t = 20)]
+    /// Max number of moves, at most the longest action sequence
+    #[arg(short, long, default_value_t = DEFAULT_MOVES)]
     moves: usize,
 
-    /// Read boards as lines from stdin
+    /// Allow every board the maximum number of moves. The search still stops at the
+    /// shortest solution, so easy boards finish early
+    #[arg(long, conflicts_with = "moves")]
+    auto_moves: bool,
+
+    /// Read boards as lines from stdin, skipping blank lines and comments starting with '#'
     #[arg(short, long)]
     stdin: bool,
 
-    #[arg(help = "Example: \"18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0\"")]
+    /// Only print the board with the longest shortest solution, preferring fewer of those
+    #[arg(long, conflicts_with_all = ["format", "json", "count_only", "replay", "save_replay"])]
+    find_hardest: bool,
+
+    /// Read every board from stdin as one line per row, ended by a blank line or its last row
+    #[arg(long, requires = "stdin")]
+    block: bool,
+
+    /// Only report malformed boards by line number without solving
+    #[arg(long)]
+    validate_only: bool,
+
+    /// Reject boards no game can go on from, such as already lost ones
+    #[arg(long)]
+    strict: bool,
+
+    /// Give up a board after visiting more than this many states
+    #[arg(long)]
+    limit_visited: Option<usize>,
+
+    /// Split every argument or stdin line into several boards at this character
+    #[arg(long, value_parser = parse_board_sep)]
+    board_sep: Option<char>,
+
+    /// Stop after this many boards
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Print a summary of the batch to stderr
+    #[arg(long)]
+    summary: bool,
+
+    /// Keep going after malformed boards and fail only at the end of the batch
+    #[arg(long)]
+    keep_going: bool,
+
+    /// Print the board after every move of a solution
+    #[arg(long)]
+    replay: bool,
+
+    /// Print only the number of moves of each solution
+    #[arg(long, conflicts_with = "replay")]
+    count_only: bool,
+
+    /// Search algorithm
+    #[arg(long, value_enum, default_value_t = Algo::Bfs)]
+    algo: Algo,
+
+    /// Output format of the outcome of every board
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with_all = ["count_only", "replay"])]
+    format: OutputFormat,
+
+    /// Same as --format json
+    #[arg(long, conflicts_with_all = ["count_only", "replay", "format"])]
+    json: bool,
+
+    /// Replace walks of the cursor over unchanged cells by a single "goto" step in JSON
+    #[arg(long)]
+    collapse_walks: bool,
+
+    /// Also append a JSON object per solved board to this file, whatever the --format
+    #[arg(long)]
+    json_out: Option<PathBuf>,
+
+    /// Append every unsolved or malformed board of --stdin with the reason to this file
+    /// instead of printing it
+    #[arg(long, requires = "stdin", conflicts_with = "find_hardest")]
+    unsolved_out: Option<PathBuf>,
+
+    /// Record the progress of --stdin after every line in this file
+    #[arg(long, requires = "stdin", conflicts_with = "find_hardest")]
+    checkpoint: Option<PathBuf>,
+
+    /// Skip the lines recorded in the --checkpoint file, which must start the same input
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+
+    /// Solve symmetric variants of a board only once and print the cache hits to stderr
+    #[arg(long)]
+    cache_stats: bool,
+
+    /// Print the shortest solution at this index, ordering them by moves Up, Down, Left, Right
+    #[arg(long, conflicts_with_all = ["stdin", "find_hardest"])]
+    solution_index: Option<u64>,
+
+    /// Print the number of new boards after every move of the search to stderr
+    #[arg(long, conflicts_with = "stdin")]
+    layer_sizes: bool,
+
+    /// Print the time spent on setup, on searching and on reading and printing to stderr
+    #[arg(long)]
+    profile: bool,
+
+    /// Color the printed boards
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+
+    /// Write the solution of the single board argument to a replay file
+    #[arg(long, conflicts_with = "stdin")]
+    save_replay: Option<PathBuf>,
+
+    #[arg(
+        help = "Example: \"18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0\" (append \"@12\" to override --moves)"
+    )]
     board: Vec<String>,
 }
 
+fn parse_board_sep(s: &str) -> Result<char, String> {
+    match s.parse::<char>() {
+        Ok('|' | ' ') => Err("must differ from the board's row and cell separators".into()),
+        Ok(c) => Ok(c),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/**
+Split an optional `@<moves>` budget suffix off a board argument.
+*/
+fn split_budget(s: &str) -> Result<(&str, Option<usize>), String> {
+    match s.rsplit_once('@') {
+        Some((board, budget)) => match budget.trim().parse::<usize>() {
+            Ok(moves) if moves <= ActionSequence::MAX_LENGTH => Ok((board, Some(moves))),
+            _ => Err(format!(
+                "Invalid: Move budget '@{budget}' must be a number up to {}!",
+                ActionSequence::MAX_LENGTH
+            )),
+        },
+        None => Ok((s, None)),
+    }
+}
+
+fn split_boards(s: &str, sep: Option<char>) -> Vec<&str> {
+    match sep {
+        Some(c) => s.split(c).filter(|b| !b.trim().is_empty()).collect(),
+        None => vec![s],
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+/**
+Join each block of up to `N` non-blank lines into a single `|`-separated board line.
+Cells of a row may be separated by any whitespace.
+*/
+fn join_blocks(
+    mut lines: impl Iterator<Item = io::Result<String>>,
+) -> impl Iterator<Item = io::Result<String>> {
+    std::iter::from_fn(move || {
+        let mut rows = vec![];
+        loop {
+            match lines.next() {
+                Some(Ok(line)) if is_comment(&line) => {}
+                Some(Ok(line)) if line.trim().is_empty() => {
+                    if !rows.is_empty() {
+                        break;
+                    }
+                }
+                Some(Ok(line)) => {
+                    rows.push(line.split_whitespace().join(" "));
+                    if rows.len() == N {
+                        break;
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None if rows.is_empty() => return None,
+                None => break,
+            }
+        }
+        Some(Ok(rows.join("|")))
+    })
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct Summary {
+    boards: usize,
+    solved: usize,
+    unsolvable: usize,
+    limit_reached: usize,
+    parse_errors: usize,
+}
+
+impl Summary {
+    fn count(&mut self, outcome: &SolveOutcome) {
+        match outcome {
+            SolveOutcome::Solved(_) => self.solved += 1,
+            SolveOutcome::Unsolvable => self.unsolvable += 1,
+            SolveOutcome::LimitReached => self.limit_reached += 1,
+            SolveOutcome::Aborted { .. } => {}
+        }
+    }
+
+    fn print(&self, err: &mut impl Write, elapsed: Duration) -> io::Result<()> {
+        writeln!(
+            err,
+            "Summary: {} boards, {} solved, {} unsolvable, {} search limit reached, {} parse errors in {:.3}s",
+            self.boards,
+            self.solved,
+            self.unsolvable,
+            self.limit_reached,
+            self.parse_errors,
+            elapsed.as_secs_f64()
+        )
+    }
+}
+
+/**
+Progress of a batch: the number of input lines done, the hash of those lines and
+the summary of their boards.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Checkpoint {
+    line: usize,
+    hash: u64,
+    summary: Summary,
+}
+
+impl Checkpoint {
+    const START_HASH: u64 = 0xcbf2_9ce4_8422_2325;
+
+    /**
+    FNV-1a hash of the lines so far, extended by `line`.
+    */
+    fn hash_line(hash: u64, line: &str) -> u64 {
+        line.bytes()
+            .chain([b'\n'])
+            .fold(hash, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    /**
+    Replace the file at `path` by lines `KEY=<value>`, written to a temporary file first
+    so that a crash never leaves a partial checkpoint.
+    */
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let s = &self.summary;
+        let tmp = path.with_extension("tmp");
+        fs::write(
+            &tmp,
+            format!(
+                "LINE={}\nHASH={:#018x}\nBOARDS={}\nSOLVED={}\nUNSOLVABLE={}\nLIMIT_REACHED={}\nPARSE_ERRORS={}\n",
+                self.line, self.hash, s.boards, s.solved, s.unsolvable, s.limit_reached, s.parse_errors
+            ),
+        )?;
+        fs::rename(tmp, path)
+    }
+
+    /**
+    The checkpoint at `path`, or None if there is no such file.
+    */
+    fn read(path: &Path) -> io::Result<Option<Self>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let field = |key: &str| -> io::Result<u64> {
+            text.lines()
+                .find_map(|l| l.strip_prefix(key)?.strip_prefix('='))
+                .and_then(|v| match v.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                    None => v.parse().ok(),
+                })
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("checkpoint has no valid {key}="),
+                    )
+                })
+        };
+        let count = |key: &str| field(key).map(|v| v as usize);
+        Ok(Some(Checkpoint {
+            line: count("LINE")?,
+            hash: field("HASH")?,
+            summary: Summary {
+                boards: count("BOARDS")?,
+                solved: count("SOLVED")?,
+                unsolvable: count("UNSOLVABLE")?,
+                limit_reached: count("LIMIT_REACHED")?,
+                parse_errors: count("PARSE_ERRORS")?,
+            },
+        }))
+    }
+}
+
+/**
+Stop solving on Ctrl-C. A second Ctrl-C exits right away,
+e.g. while waiting for the next line on stdin.
+*/
+#[cfg(feature = "interrupt")]
+mod interrupt {
+    use std::process::exit;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    pub fn install() -> Option<Arc<AtomicBool>> {
+        let flag = Arc::new(AtomicBool::new(false));
+        let handler_flag = flag.clone();
+        ctrlc::set_handler(move || {
+            if handler_flag.swap(true, Ordering::Relaxed) {
+                exit(super::INTERRUPTED_EXIT_CODE);
+            }
+        })
+        .ok()?;
+        Some(flag)
+    }
+}
+
+#[cfg(not(feature = "interrupt"))]
+mod interrupt {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    pub fn install() -> Option<Arc<AtomicBool>> {
+        None
+    }
+}
+
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/**
+The board and its outcome as a JSON object on a single line.
+*/
+fn json_line(board: &Board, outcome: &SolveOutcome, collapse: bool) -> String {
+    match outcome {
+        SolveOutcome::Solved(actions) => {
+            let moves = if collapse {
+                collapse_walks(board, actions)
+                    .into_iter()
+                    .map(|step| match step {
+                        Step::Move(action) => format!("\"{action}\""),
+                        Step::GoTo(p) => format!("{{\"goto\":[{},{}]}}", p.row(), p.column()),
+                    })
+                    .join(",")
+            } else {
+                actions.iter().map(|a| format!("\"{a}\"")).join(",")
+            };
+            format!("{{\"board\":\"{board}\",\"outcome\":\"solved\",\"moves\":[{moves}]}}")
+        }
+        SolveOutcome::Unsolvable => format!("{{\"board\":\"{board}\",\"outcome\":\"unsolvable\"}}"),
+        SolveOutcome::LimitReached => {
+            format!("{{\"board\":\"{board}\",\"outcome\":\"limit_reached\"}}")
+        }
+        SolveOutcome::Aborted { .. } => {
+            format!("{{\"board\":\"{board}\",\"outcome\":\"aborted\"}}")
+        }
+    }
+}
+
+struct Hardest {
+    board: Board,
+    actions: Vec<Action>,
+    count: Option<u64>,
+}
+
+impl Hardest {
+    /**
+    The number of shortest solutions, counted only once it breaks a tie.
+    */
+    fn count(&mut self) -> u64 {
+        *self.count.get_or_insert_with(|| {
+            count_shortest_solutions(&self.board, self.actions.len()).unwrap_or(0)
+        })
+    }
+
+    fn consider(best: &mut Option<Hardest>, board: Board, actions: Vec<Action>) {
+        let mut candidate = Hardest {
+            board,
+            actions,
+            count: None,
+        };
+        let harder = match best {
+            None => true,
+            Some(best) => match candidate.actions.len().cmp(&best.actions.len()) {
+                Ordering::Greater => true,
+                Ordering::Less => false,
+                Ordering::Equal => candidate.count() < best.count(),
+            },
+        };
+        if harder {
+            *best = Some(candidate);
+        }
+    }
+}
+
+/**
+The solution as the hexadecimal bits of its `ActionSequence`.
+*/
+fn packed_line(outcome: &SolveOutcome) -> String {
+    match outcome {
+        SolveOutcome::Solved(actions) => {
+            let seq: ActionSequence = actions.iter().copied().collect();
+            format!("{:#018x}", seq.bits())
+        }
+        SolveOutcome::Unsolvable => "X".into(),
+        SolveOutcome::LimitReached | SolveOutcome::Aborted { .. } => "?".into(),
+    }
+}
+
+/**
+Measure the time `inner` takes for all boards together.
+*/
+struct Timed<'a> {
+    inner: &'a dyn Solve,
+    boards: Cell<usize>,
+    elapsed: Cell<Duration>,
+}
+
+impl<'a> Timed<'a> {
+    fn new(inner: &'a dyn Solve) -> Self {
+        Self {
+            inner,
+            boards: Cell::new(0),
+            elapsed: Cell::new(Duration::ZERO),
+        }
+    }
+}
+
+impl Solve for Timed<'_> {
+    fn solve(&self, board: &Board, max_moves: usize) -> Option<Vec<Action>> {
+        self.solve_outcome(board, max_moves).solution()
+    }
+
+    fn solve_outcome(&self, board: &Board, max_moves: usize) -> SolveOutcome {
+        let start = Instant::now();
+        let outcome = self.inner.solve_outcome(board, max_moves);
+        self.elapsed.set(self.elapsed.get() + start.elapsed());
+        self.boards.set(self.boards.get() + 1);
+        outcome
+    }
+}
+
+/**
+Warning for a solution which takes all `moves` of the budget.
+*/
+fn budget_warning(outcome: &SolveOutcome, moves: usize) -> Option<String> {
+    match outcome {
+        SolveOutcome::Solved(actions) if moves > 0 && actions.len() == moves => Some(format!(
+            "Warning: Solution takes the whole budget of {moves} moves. The search still guarantees \
+             that no shorter solution exists, the budget only rules out longer ones."
+        )),
+        _ => None,
+    }
+}
+
+/**
+How `run_batch` solves and prints every board.
+*/
+struct Batch<'a> {
+    solver: &'a dyn Solve,
+    moves: usize,
+    format: OutputFormat,
+    collapse_walks: bool,
+    count_only: bool,
+    find_hardest: bool,
+    strict: bool,
+    /// Count malformed boards instead of stopping at the first one
+    keep_going: bool,
+    board_sep: Option<char>,
+    /// Stop after this many boards
+    limit: Option<usize>,
+    /// Pick this shortest solution instead of the first one
+    solution_index: Option<u64>,
+    checkpoint: Option<&'a Path>,
+    /// Skip the lines of this checkpoint and continue its summary
+    resume: Option<Checkpoint>,
+}
+
+/**
+What is left after `run_batch`: the exit code is set if the batch stopped early.
+*/
+struct BatchEnd {
+    summary: Summary,
+    hardest: Option<Hardest>,
+    exit_code: Option<i32>,
+}
+
+/**
+Solve the boards of `lines`, writing one line per board to `out` and errors to `err`.
+Blank lines and comments are skipped. The hardest board is only collected, not printed.
+Every solved board is also written to `json_out` as in `--format json`.
+Every other board is written to `unsolved_out` with the reason instead of to `out`.
+*/
+fn run_batch(
+    lines: impl Iterator<Item = io::Result<String>>,
+    batch: &Batch,
+    out: &mut impl Write,
+    err: &mut impl Write,
+    mut json_out: Option<&mut dyn Write>,
+    mut unsolved_out: Option<&mut dyn Write>,
+) -> io::Result<BatchEnd> {
+    let mut end = BatchEnd {
+        summary: batch
+            .resume
+            .as_ref()
+            .map_or_else(Summary::default, |c| c.summary.clone()),
+        hardest: None,
+        exit_code: None,
+    };
+    let skip = batch.resume.as_ref().map_or(0, |c| c.line);
+    let mut hash = Checkpoint::START_HASH;
+    let mut read = 0;
+    let mut remaining = batch.limit.unwrap_or(usize::MAX);
+    for (i, line_r) in lines.enumerate() {
+        read = i + 1;
+        if let Ok(line) = &line_r {
+            hash = Checkpoint::hash_line(hash, line);
+        }
+        if i < skip {
+            if i + 1 == skip && batch.resume.as_ref().is_some_and(|c| c.hash != hash) {
+                writeln!(err, "Invalid: Input differs from the checkpoint!")?;
+                end.exit_code = Some(2);
+                return Ok(end);
+            }
+            continue;
+        }
+        let line = match line_r {
+            Ok(line) if line.trim().is_empty() || is_comment(&line) => continue,
+            Ok(line) => line,
+            Err(e) => {
+                writeln!(err, "Error: {e}")?;
+                continue;
+            }
+        };
+        for board_str in split_boards(&line, batch.board_sep)
+            .into_iter()
+            .take(remaining)
+        {
+            remaining -= 1;
+            let solved = solve_batch_board(
+                board_str,
+                batch.moves,
+                batch,
+                &mut end,
+                out,
+                err,
+                &mut json_out,
+                &mut unsolved_out,
+            )?;
+            if end.exit_code.is_some() {
+                return Ok(end);
+            }
+            match solved {
+                Some((_, SolveOutcome::Solved(actions))) if batch.count_only => {
+                    writeln!(out, "{}", actions.len())?;
+                }
+                Some((_, SolveOutcome::Solved(actions))) => {
+                    writeln!(out, "{}", join(&actions, ","))?
+                }
+                Some((_, SolveOutcome::Unsolvable)) => writeln!(out, "X")?,
+                Some((_, SolveOutcome::LimitReached)) => writeln!(out, "?")?,
+                Some((_, SolveOutcome::Aborted { .. })) | None => {}
+            }
+        }
+        if let Some(path) = batch.checkpoint {
+            let checkpoint = Checkpoint {
+                line: i + 1,
+                hash,
+                summary: end.summary.clone(),
+            };
+            checkpoint.write(path)?;
+        }
+        if remaining == 0 {
+            break;
+        }
+    }
+    if read < skip {
+        writeln!(err, "Invalid: Input is shorter than the checkpoint!")?;
+        end.exit_code = Some(2);
+    }
+    Ok(end)
+}
+
+/**
+Report every malformed board of `lines` to `out` by its line number, without solving.
+Lines failing to read are reported to `err` and count as malformed, but as in `run_batch`
+the lines after them are still checked. Returns whether every board is well-formed.
+With `budgets` the boards may end with a move budget like board arguments (see `split_budget`).
+*/
+fn validate_boards(
+    lines: impl Iterator<Item = io::Result<String>>,
+    board_sep: Option<char>,
+    budgets: bool,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> io::Result<bool> {
+    let mut valid = true;
+    for (i, line_r) in lines.enumerate() {
+        let line = match line_r {
+            Ok(line) if line.trim().is_empty() || is_comment(&line) => continue,
+            Ok(line) => line,
+            Err(e) => {
+                writeln!(err, "Error: Line {}: {e}", i + 1)?;
+                valid = false;
+                continue;
+            }
+        };
+        for board_str in split_boards(&line, board_sep) {
+            let board_str = match split_budget(board_str) {
+                Ok((board_str, _)) if budgets => board_str,
+                Err(e) if budgets => {
+                    writeln!(out, "Line {}: {e}", i + 1)?;
+                    valid = false;
+                    continue;
+                }
+                _ => board_str,
+            };
+            if let Err(e) = board_str.trim().parse::<Board>() {
+                writeln!(out, "Line {}: {e:?} ({e})", i + 1)?;
+                valid = false;
+            }
+        }
+    }
+    Ok(valid)
+}
+
+/**
+Solve `board_str` as a board of a batch within `moves`, counting it in `end`.
+Warnings and errors go to `err`, and `end.exit_code` is set if the batch has to stop.
+Machine formats are written to `out` right away and the hardest board is only collected.
+The other boards are returned with their outcome to print as text.
+*/
+#[allow(clippy::too_many_arguments)]
+fn solve_batch_board(
+    board_str: &str,
+    moves: usize,
+    batch: &Batch,
+    end: &mut BatchEnd,
+    out: &mut impl Write,
+    err: &mut impl Write,
+    json_out: &mut Option<&mut dyn Write>,
+    unsolved_out: &mut Option<&mut dyn Write>,
+) -> io::Result<Option<(Board, SolveOutcome)>> {
+    end.summary.boards += 1;
+    let Ok(board) = board_str.trim().parse::<Board>() else {
+        writeln!(err, "Invalid: Failed to parse board!")?;
+        write_unsolved(unsolved_out, board_str, "Failed to parse board")?;
+        end.summary.parse_errors += 1;
+        if !batch.keep_going {
+            end.exit_code = Some(2);
+        }
+        return Ok(None);
+    };
+    if batch.strict && !board.is_valid() {
+        writeln!(err, "Invalid: Board {board} is not a valid game state!")?;
+        write_unsolved(unsolved_out, board_str, "Not a valid game state")?;
+        end.exit_code = Some(2);
+        return Ok(None);
+    }
+    let outcome = match (
+        batch.solution_index,
+        batch.solver.solve_outcome(&board, moves),
+    ) {
+        (Some(index), SolveOutcome::Solved(actions)) => {
+            match nth_shortest_solution(&board, actions.len(), index) {
+                Some(nth) => SolveOutcome::Solved(nth),
+                None => {
+                    writeln!(
+                        err,
+                        "Invalid: Board {board} has only {} shortest solutions!",
+                        count_shortest_solutions(&board, actions.len()).unwrap_or(0)
+                    )?;
+                    end.exit_code = Some(2);
+                    return Ok(None);
+                }
+            }
+        }
+        (_, outcome) => outcome,
+    };
+    end.summary.count(&outcome);
+    if let Some(warning) = budget_warning(&outcome, moves) {
+        writeln!(err, "{warning}")?;
+    }
+    if let (Some(json), SolveOutcome::Solved(_)) = (json_out.as_mut(), &outcome) {
+        writeln!(
+            json,
+            "{}",
+            json_line(&board, &outcome, batch.collapse_walks)
+        )?;
+    }
+    let reason = match outcome {
+        SolveOutcome::Unsolvable => Some(format!("Unsolvable within {moves} moves")),
+        SolveOutcome::LimitReached => Some("Search limit reached".to_string()),
+        _ => None,
+    };
+    if let Some(reason) = reason.filter(|_| unsolved_out.is_some()) {
+        write_unsolved(unsolved_out, board_str, &reason)?;
+        return Ok(None);
+    }
+    match outcome {
+        SolveOutcome::Aborted { depth, visited } => {
+            writeln!(
+                err,
+                "Interrupted: Searched all move sequences up to {depth} moves and visited {visited} boards."
+            )?;
+            end.exit_code = Some(INTERRUPTED_EXIT_CODE);
+        }
+        _ if batch.format == OutputFormat::Json => {
+            writeln!(out, "{}", json_line(&board, &outcome, batch.collapse_walks))?;
+        }
+        _ if batch.format == OutputFormat::Packed => {
+            writeln!(out, "{}", packed_line(&outcome))?;
+        }
+        SolveOutcome::Solved(actions) if batch.find_hardest => {
+            Hardest::consider(&mut end.hardest, board, actions);
+        }
+        _ if batch.find_hardest => {}
+        outcome => return Ok(Some((board, outcome))),
+    }
+    Ok(None)
+}
+
+/**
+Write `board_str` to `unsolved_out` after a comment with the reason, so the file
+can be read again with `--stdin`.
+*/
+fn write_unsolved(
+    unsolved_out: &mut Option<&mut dyn Write>,
+    board_str: &str,
+    reason: &str,
+) -> io::Result<()> {
+    match unsolved_out {
+        Some(unsolved) => writeln!(unsolved, "# {reason}\n{}", board_str.trim()),
+        None => Ok(()),
+    }
+}
+
+/**
+Render the board as a grid with the cursor in brackets.
+With color, nonzero cells are highlighted and the cursor is inverted.
+*/
+fn render(board: &Board, color: bool) -> String {
+    (0..N)
+        .map(|r| {
+            (0..N)
+                .map(|c| {
+                    let p = Point::from(r, c);
+                    let v = board.cell(p);
+                    let text = format!("{v:>3}");
+                    match (color, board.cursor() == p, v) {
+                        (true, true, _) => format!("\x1b[7m[{text}]\x1b[0m"),
+                        (true, false, 0) => format!(" \x1b[2m{text}\x1b[0m "),
+                        (true, false, _) => format!(" \x1b[1;33m{text}\x1b[0m "),
+                        (false, true, _) => format!("[{text}]"),
+                        (false, false, _) => format!(" {text} "),
+                    }
+                })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .join("\n")
+}
+
 fn main() {
+    let launched = Instant::now();
     let args = Cli::parse();
     if args.moves > ActionSequence::MAX_LENGTH {
-        eprintln!("Invalid: Max supported moves: {}", ActionSequence::MAX_LENGTH);
+        eprintln!(
+            "Invalid: Max supported moves: {}",
+            ActionSequence::MAX_LENGTH
+        );
         exit(1);
     }
-    if args.stdin {
-        let lines = io::stdin().lines();
-        for line_r in lines {
-            match line_r {
-                Ok(line) => {
-                    if let Ok(board) = line.trim().parse::<Board>() {
-                        if let Some(actions) = solve_board(&board, args.moves) {
-                            let action_str = join(&actions, ",");
-                            println!("{action_str}");
-                        } else {
-                            println!("X");
-                        }
-                    } else {
-                        eprintln!("Invalid: Failed to parse board!");
-                        exit(2);
-                    }
-                }
-                Err(e) => eprintln!("Error: {e}"),
+    if args.layer_sizes && args.algo != Algo::Bfs {
+        eprintln!("Invalid: --layer-sizes needs --algo bfs!");
+        exit(1);
+    }
+    let layer_sizes = args.layer_sizes.then(|| Arc::new(Mutex::new(vec![])));
+    let config = SolverConfig::default()
+        .visited_limit(args.limit_visited)
+        .abort(interrupt::install())
+        .record_layer_sizes(layer_sizes.clone());
+    let algo: Box<dyn Solve> = match args.algo {
+        Algo::Bfs => Box::new(Bfs(config)),
+        Algo::Iddfs => Box::new(Iddfs),
+    };
+    let cache = args.cache_stats.then(|| Cached::new(algo.as_ref()));
+    let solver: &dyn Solve = match &cache {
+        Some(cache) => cache,
+        None => algo.as_ref(),
+    };
+    let timed = args.profile.then(|| Timed::new(solver));
+    let solver: &dyn Solve = match &timed {
+        Some(timed) => timed,
+        None => solver,
+    };
+    let color = match args.color {
+        ColorMode::Auto => io::stdout().is_terminal(),
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+    };
+    let format = if args.json {
+        OutputFormat::Json
+    } else {
+        args.format
+    };
+    if args.collapse_walks && format != OutputFormat::Json {
+        eprintln!("Invalid: --collapse-walks needs --format json!");
+        exit(1);
+    }
+    let moves = if args.auto_moves {
+        ActionSequence::MAX_LENGTH
+    } else {
+        args.moves
+    };
+    let open_append = |path: &PathBuf| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|e| {
+                eprintln!("Error: Failed to open {}: {e}", path.display());
+                exit(1);
+            })
+    };
+    let mut json_out = args.json_out.as_ref().map(open_append);
+    let mut unsolved_out = args.unsolved_out.as_ref().map(open_append);
+    let start = Instant::now();
+    let mut summary = Summary::default();
+    let mut hardest: Option<Hardest> = None;
+    let print_summary = |summary: &Summary| {
+        summary
+            .print(&mut io::stderr(), start.elapsed())
+            .expect("should write to stderr");
+    };
+    let stop_early = |end: &BatchEnd| {
+        if let Some(code) = end.exit_code {
+            if code == INTERRUPTED_EXIT_CODE && args.summary {
+                print_summary(&end.summary);
             }
+            exit(code);
         }
-    } else if args.board.len() > 0 {
-        for board_str in args.board {
-            if let Ok(board) = board_str.parse::<Board>() {
-                if let Some(actions) = solve_board(&board, args.moves) {
+    };
+    let mut batch = Batch {
+        solver,
+        moves,
+        format,
+        collapse_walks: args.collapse_walks,
+        count_only: args.count_only,
+        find_hardest: args.find_hardest,
+        strict: args.strict,
+        keep_going: args.keep_going,
+        board_sep: args.board_sep,
+        limit: args.limit,
+        solution_index: args.solution_index,
+        checkpoint: args.checkpoint.as_deref(),
+        resume: None,
+    };
+    let stdin_lines = || -> Box<dyn Iterator<Item = io::Result<String>>> {
+        if args.block {
+            Box::new(join_blocks(io::stdin().lines()))
+        } else {
+            Box::new(io::stdin().lines())
+        }
+    };
+    if args.validate_only {
+        let lines: Box<dyn Iterator<Item = io::Result<String>>> = if args.stdin {
+            stdin_lines()
+        } else {
+            Box::new(args.board.clone().into_iter().map(Ok))
+        };
+        let valid = validate_boards(
+            lines,
+            args.board_sep,
+            !args.stdin,
+            &mut io::stdout(),
+            &mut io::stderr(),
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            exit(1);
+        });
+        if !valid {
+            exit(2);
+        }
+    } else if args.stdin {
+        batch.resume = match &args.checkpoint {
+            Some(path) if args.resume => Checkpoint::read(path).unwrap_or_else(|e| {
+                eprintln!("Error: Failed to read checkpoint: {e}");
+                exit(2);
+            }),
+            _ => None,
+        };
+        let lines = stdin_lines();
+        let json_out = json_out.as_mut().map(|f| f as &mut dyn Write);
+        let unsolved_out = unsolved_out.as_mut().map(|f| f as &mut dyn Write);
+        let end = run_batch(
+            lines,
+            &batch,
+            &mut io::stdout(),
+            &mut io::stderr(),
+            json_out,
+            unsolved_out,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            exit(1);
+        });
+        stop_early(&end);
+        summary = end.summary;
+        hardest = end.hardest;
+    } else if !args.board.is_empty() {
+        let board_strs: Vec<&str> = args
+            .board
+            .iter()
+            .flat_map(|arg| split_boards(arg, args.board_sep))
+            .take(args.limit.unwrap_or(usize::MAX))
+            .collect();
+        if args.save_replay.is_some() && board_strs.len() > 1 {
+            eprintln!("Invalid: --save-replay needs a single board!");
+            exit(1);
+        }
+        if args.layer_sizes && board_strs.len() > 1 {
+            eprintln!("Invalid: --layer-sizes needs a single board!");
+            exit(1);
+        }
+        let mut end = BatchEnd {
+            summary: Summary::default(),
+            hardest: None,
+            exit_code: None,
+        };
+        let mut json_out = json_out.as_mut().map(|f| f as &mut dyn Write);
+        for board_str in board_strs {
+            let (board_str, budget) = split_budget(board_str).unwrap_or_else(|e| {
+                eprintln!("{e}");
+                exit(2);
+            });
+            if board_str.trim().is_empty() {
+                eprintln!("Invalid: Empty board!");
+                exit(2);
+            }
+            let solved = solve_batch_board(
+                board_str,
+                budget.unwrap_or(moves),
+                &batch,
+                &mut end,
+                &mut io::stdout(),
+                &mut io::stderr(),
+                &mut json_out,
+                &mut None,
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {e}");
+                exit(1);
+            });
+            stop_early(&end);
+            match solved {
+                Some((_, SolveOutcome::Solved(actions))) if args.count_only => {
+                    println!("{}", actions.len());
+                }
+                Some((_, SolveOutcome::Unsolvable)) if args.count_only => println!("X"),
+                Some((_, SolveOutcome::LimitReached)) if args.count_only => println!("?"),
+                Some((board, SolveOutcome::Solved(actions))) => {
                     let action_str = join(&actions, ", ");
                     println!("Solution with {} moves: {action_str}", actions.len());
-                } else {
-                    println!("No solution!");
+                    if let Some(path) = &args.save_replay {
+                        if let Err(e) = write_replay(path, &board, &actions) {
+                            eprintln!("Error: Failed to write replay: {e}");
+                            exit(1);
+                        }
+                    }
+                    if args.replay {
+                        let mut current = board;
+                        println!("{}", render(&current, color));
+                        for action in actions {
+                            current = current.action(action).unwrap();
+                            println!("{action}:\n{}", render(&current, color));
+                        }
+                    }
                 }
-            } else {
-                eprintln!("Invalid: Failed to parse board!");
-                exit(2);
+                Some((_, SolveOutcome::Unsolvable)) => println!("No solution!"),
+                Some((_, SolveOutcome::LimitReached)) => println!("Search limit reached!"),
+                Some((_, SolveOutcome::Aborted { .. })) | None => {}
             }
         }
+        summary = end.summary;
+        hardest = end.hardest;
     } else {
         println!("No board to solve. Try --help.");
         exit(3);
     }
+    if args.find_hardest {
+        match hardest {
+            Some(mut h) => {
+                let count = h.count();
+                println!(
+                    "Hardest board {} with {} moves ({count} shortest solutions): {}",
+                    h.board,
+                    h.actions.len(),
+                    join(&h.actions, ", ")
+                );
+            }
+            None => println!("No board solved!"),
+        }
+    }
+    if let Some(sizes) = &layer_sizes {
+        eprintln!("Layers: {}", sizes.lock().unwrap().iter().join(","));
+    }
+    if let Some(timed) = &timed {
+        let search = timed.elapsed.get();
+        eprintln!(
+            "Profile: Setup in {:.3}s, searched {} boards in {:.3}s, reading and printing in {:.3}s",
+            start.duration_since(launched).as_secs_f64(),
+            timed.boards.get(),
+            search.as_secs_f64(),
+            start.elapsed().saturating_sub(search).as_secs_f64()
+        );
+    }
+    if let Some(cache) = &cache {
+        let stats = cache.stats();
+        eprintln!(
+            "Cache: {} hits, {} symmetric hits, {} misses",
+            stats.hits, stats.symmetric_hits, stats.misses
+        );
+    }
+    if args.summary {
+        print_summary(&summary);
+    }
+    if summary.parse_errors > 0 {
+        exit(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_into_buffers() {
+        let solver = Bfs(SolverConfig::default());
+        let mut batch = Batch {
+            solver: &solver,
+            moves: 8,
+            format: OutputFormat::Text,
+            collapse_walks: false,
+            count_only: false,
+            find_hardest: false,
+            strict: false,
+            keep_going: true,
+            board_sep: None,
+            limit: None,
+            solution_index: None,
+            checkpoint: None,
+            resume: None,
+        };
+        let input = [
+            "# easy first",
+            "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0",
+            "",
+            "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0",
+            "not a board",
+            "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0",
+        ];
+        let lines = || input.iter().map(|l| Ok(l.to_string()));
+        let (mut out, mut err) = (vec![], vec![]);
+        let end = run_batch(lines(), &batch, &mut out, &mut err, None, None).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Down,Down,Down,Right\nX\nRight\n"
+        );
+        assert_eq!(
+            String::from_utf8(err).unwrap(),
+            "Invalid: Failed to parse board!\n"
+        );
+        assert_eq!(end.exit_code, None);
+        assert_eq!((end.summary.boards, end.summary.parse_errors), (4, 1));
+
+        batch.keep_going = false;
+        batch.count_only = true;
+        let (mut out, mut err) = (vec![], vec![]);
+        let end = run_batch(lines(), &batch, &mut out, &mut err, None, None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "4\nX\n");
+        assert_eq!(end.exit_code, Some(2), "should stop at the malformed board");
+
+        batch.limit = Some(1);
+        let (mut out, mut err) = (vec![], vec![]);
+        let end = run_batch(lines(), &batch, &mut out, &mut err, None, None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "4\n");
+        assert_eq!((end.summary.boards, end.exit_code), (1, None));
+    }
+
+    #[test]
+    fn validate_past_read_errors() {
+        let lines = [
+            Ok("0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0;0 0 0|0 0 0 0".to_string()),
+            Err(io::Error::new(io::ErrorKind::InvalidData, "not UTF-8")),
+            Ok("0 0 0 0|0 300 0 0|0 0 0 0|0 0 0 0".to_string()),
+        ];
+        let (mut out, mut err) = (vec![], vec![]);
+        let valid =
+            validate_boards(lines.into_iter(), Some(';'), false, &mut out, &mut err).unwrap();
+        assert!(!valid);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Line 1: WrongCellCount (board does not have 4x4 cells)\n\
+             Line 3: InvalidCell (cell is not a number from 0 to 255)\n"
+        );
+        assert_eq!(
+            String::from_utf8(err).unwrap(),
+            "Error: Line 2: not UTF-8\n"
+        );
+    }
 }
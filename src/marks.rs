@@ -0,0 +1,131 @@
+use crate::values::{Point, Transform, N};
+
+/**
+   Set of points on the board as a bitmap with one bit per cell index.
+   A board marks its walls with it, which moves never change, so the solver
+   carries no mark board to update. `Board::zero_regions` and `Board::walk_to`
+   build their own as visited sets.
+*/
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct MarkBoard(u16);
+
+/**
+   Number of mark boards which are no symmetric variants of each other, i.e. the orbits
+   of all 2^16 mark boards under the 8 symmetries of the grid. By Burnside's lemma this
+   is the average number of mark boards fixed by a symmetry:
+   (2^16 + 2 * 2^4 + 2^8 + 2 * 2^8 + 2 * 2^10) / 8.
+*/
+pub const CANONICAL_MARK_BOARDS: usize = 8548;
+
+impl MarkBoard {
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    pub fn mark(&mut self, p: Point) {
+        debug_assert!(p.inside());
+        self.0 |= 1 << p.index();
+    }
+
+    pub fn is_marked(&self, p: Point) -> bool {
+        p.inside() && self.0 & (1 << p.index()) != 0
+    }
+
+    /**
+       The marked point with the lowest cell index.
+    */
+    pub fn first(&self) -> Option<Point> {
+        (self.0 != 0).then(|| {
+            let i = self.0.trailing_zeros() as usize;
+            Point::from(i / N, i % N)
+        })
+    }
+
+    /**
+       The marks as bits by cell index.
+    */
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /**
+       The marks moved by the transform `t`.
+    */
+    pub fn symmetry(&self, t: Transform) -> Self {
+        let mut marks = Self::new();
+        for p in self.iter_marked() {
+            marks.mark(p.symmetry(t));
+        }
+        marks
+    }
+
+    /**
+       Representative of all symmetric variants: the variant with the lowest bits.
+    */
+    pub fn canonical(&self) -> Self {
+        Transform::ALL
+            .into_iter()
+            .map(|t| self.symmetry(t))
+            .min_by_key(|m| m.0)
+            .unwrap()
+    }
+
+    /**
+       The marked points by increasing cell index.
+    */
+    pub fn iter_marked(&self) -> impl Iterator<Item = Point> {
+        let bits = self.0;
+        (0..N * N)
+            .filter(move |i| bits & (1 << i) != 0)
+            .map(|i| Point::from(i / N, i % N))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mark_points() {
+        let mut marks = MarkBoard::new();
+        assert_eq!(marks.first(), None);
+        marks.mark(Point::from(2, 1));
+        marks.mark(Point::from(1, 3));
+        assert!(marks.is_marked(Point::from(2, 1)));
+        assert!(!marks.is_marked(Point::from(1, 2)));
+        assert!(!marks.is_marked(Point::from(1, N)));
+        assert_eq!(marks.first(), Some(Point::from(1, 3)));
+    }
+
+    #[test]
+    fn count_and_iterate_marks() {
+        let mut marks = MarkBoard::new();
+        assert_eq!(marks.count(), 0);
+        assert_eq!(marks.iter_marked().next(), None);
+        let points = [Point::from(0, 0), Point::from(1, 3), Point::from(3, 2)];
+        for p in points.into_iter().rev() {
+            marks.mark(p);
+        }
+        marks.mark(Point::from(1, 3));
+        assert_eq!(marks.count(), 3);
+        assert_eq!(marks.iter_marked().collect::<Vec<_>>(), points);
+    }
+
+    #[test]
+    fn count_canonical_marks() {
+        let canonical: std::collections::HashSet<MarkBoard> = (0..=u16::MAX)
+            .map(|bits| MarkBoard(bits).canonical())
+            .collect();
+        assert_eq!(canonical.len(), CANONICAL_MARK_BOARDS);
+        let corner = MarkBoard(1 << Point::from(0, N - 1).index());
+        assert_eq!(corner.canonical(), MarkBoard(1));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::action::Action;
+use crate::board::{Board, ParseBoardError};
+use crate::solve::verify_solution;
+use crate::values::{Point, N};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/**
+   Failure to read a replay file.
+*/
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// A required `KEY=` line is absent
+    MissingField(&'static str),
+    InvalidBoard(ParseBoardError),
+    /// The cursor is not given as `row col` inside the grid
+    InvalidCursor,
+    /// A move is not one of the letters `U`, `D`, `L` or `R`
+    InvalidMove(char),
+    /// The moves do not win the board
+    NotSolved,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{e}"),
+            ReplayError::MissingField(key) => write!(f, "replay has no {key}= line"),
+            ReplayError::InvalidBoard(e) => write!(f, "invalid board: {e}"),
+            ReplayError::InvalidCursor => write!(f, "cursor is not a row and column on the board"),
+            ReplayError::InvalidMove(c) => write!(f, "move '{c}' is not one of U, D, L, R"),
+            ReplayError::NotSolved => write!(f, "moves do not solve the board"),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+/**
+   Save a solution as lines `BOARD=<board>`, `CURSOR=<row> <col>` and `MOVES=<letters>`,
+   e.g. `MOVES=UDLR`.
+*/
+pub fn write_replay(path: impl AsRef<Path>, board: &Board, actions: &[Action]) -> io::Result<()> {
+    let cursor = board.cursor();
+    let moves: String = actions.iter().map(Action::letter).collect();
+    fs::write(
+        path,
+        format!(
+            "BOARD={board}\nCURSOR={} {}\nMOVES={moves}\n",
+            cursor.row(),
+            cursor.column()
+        ),
+    )
+}
+
+/**
+   Load a replay written by `write_replay` and check that its moves solve the board.
+   Without a `CURSOR=` line the cursor starts in the top left corner.
+*/
+pub fn read_replay(path: impl AsRef<Path>) -> Result<(Board, Vec<Action>), ReplayError> {
+    let content = fs::read_to_string(path)?;
+    let field = |key: &str| {
+        content
+            .lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix('='))
+            .map(str::trim)
+    };
+    let board: Board = field("BOARD")
+        .ok_or(ReplayError::MissingField("BOARD"))?
+        .parse()
+        .map_err(ReplayError::InvalidBoard)?;
+    let board = match field("CURSOR") {
+        Some(cursor) => {
+            let pos = match cursor
+                .split_whitespace()
+                .map(|n| n.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(rc) if rc.len() == 2 && rc[0] < N && rc[1] < N => Point::from(rc[0], rc[1]),
+                _ => return Err(ReplayError::InvalidCursor),
+            };
+            board.clone_with_cursor(pos)
+        }
+        None => board,
+    };
+    let actions: Vec<Action> = field("MOVES")
+        .ok_or(ReplayError::MissingField("MOVES"))?
+        .chars()
+        .map(|c| Action::from_letter(c).ok_or(ReplayError::InvalidMove(c)))
+        .collect::<Result<_, _>>()?;
+    if !verify_solution(&board, &actions) {
+        return Err(ReplayError::NotSolved);
+    }
+    Ok((board, actions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::solve_board;
+
+    #[test]
+    fn replay_round_trip() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let solution = solve_board(&board, 10).expect("should solve board");
+        let path = std::env::temp_dir().join(format!("zoysii_replay_{}.txt", std::process::id()));
+        write_replay(&path, &board, &solution).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "BOARD=0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\nCURSOR=0 0\nMOVES=DDDR\n"
+        );
+        let (read_board, read_solution) = read_replay(&path).unwrap();
+        assert_eq!(read_board, board);
+        assert_eq!(read_solution, solution);
+        assert!(verify_solution(&read_board, &read_solution));
+
+        write_replay(&path, &board, &solution[..3]).unwrap();
+        assert!(matches!(read_replay(&path), Err(ReplayError::NotSolved)));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+use crate::action::{Action, ActionSequence};
+use crate::board::Board;
+
+/**
+   Interactive game with undo/redo which is lost once the move budget is exhausted.
+*/
+#[derive(Clone, Debug)]
+pub struct GameSession {
+    boards: Vec<Board>,
+    actions: Vec<Action>,
+    undone: Vec<Action>,
+    budget: usize,
+}
+
+impl GameSession {
+    pub fn new(board: Board, budget: usize) -> Self {
+        assert!(budget <= ActionSequence::MAX_LENGTH);
+        Self {
+            boards: vec![board],
+            actions: vec![],
+            undone: vec![],
+            budget,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        self.boards.last().unwrap()
+    }
+
+    /**
+       Apply `action` unless the game is over or the action leaves the grid.
+       Playing discards the moves which could be redone.
+    */
+    pub fn play(&mut self, action: Action) -> bool {
+        let played = self.apply(action);
+        if played {
+            self.undone.clear();
+        }
+        played
+    }
+
+    fn apply(&mut self, action: Action) -> bool {
+        if self.is_won() || self.is_lost() {
+            return false;
+        }
+        match self.board().action(action) {
+            Some(next) => {
+                self.boards.push(next);
+                self.actions.push(action);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<Action> {
+        let action = self.actions.pop()?;
+        self.boards.pop();
+        self.undone.push(action);
+        Some(action)
+    }
+
+    pub fn redo(&mut self) -> Option<Action> {
+        let action = self.undone.pop()?;
+        self.apply(action).then_some(action)
+    }
+
+    pub fn history(&self) -> ActionSequence {
+        self.actions
+            .iter()
+            .fold(ActionSequence::new(), |seq, a| seq.add(*a))
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn moves_left(&self) -> usize {
+        self.budget - self.move_count()
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.board().is_won()
+    }
+
+    /**
+       The game is lost on a dead cell or when the budget is used up without winning.
+    */
+    pub fn is_lost(&self) -> bool {
+        self.board().is_lost() || (self.moves_left() == 0 && !self.is_won())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_session() {
+        let board: Board = "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let mut session = GameSession::new(board, 4);
+        let moves = [Action::RIGHT, Action::DOWN, Action::DOWN];
+        for a in moves {
+            assert!(session.play(a));
+        }
+        assert!(session.play(Action::UP));
+        assert_eq!(session.move_count(), 4);
+        assert!(session.is_lost(), "should exhaust budget");
+        assert_eq!(session.undo(), Some(Action::UP));
+        assert_eq!(session.move_count(), 3);
+        let history: Vec<Action> = session.history().into();
+        assert_eq!(history, moves);
+        assert_eq!(session.undo(), Some(Action::DOWN));
+        assert_eq!(session.redo(), Some(Action::DOWN));
+        assert_eq!(session.move_count(), 3);
+        assert_eq!(session.undo(), Some(Action::DOWN));
+        assert!(session.play(Action::RIGHT));
+        assert_eq!(session.redo(), None, "should discard redo after playing");
+        assert!(session.is_won());
+        assert!(!session.is_lost());
+        assert!(!session.play(Action::LEFT), "should end after win");
+    }
+}
@@ -1,7 +1,247 @@
 use crate::action::{Action, ActionSequence, ACTIONS};
 use crate::board::Board;
+#[cfg(feature = "walls")]
+use crate::marks::MarkBoard;
+use crate::rng::Rng;
+use crate::values::{Point, Transform, N};
+use itertools::{Either, Itertools};
 use rayon::prelude::*;
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/**
+Order in which the children of a search layer are explored.
+*/
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum ChildOrder {
+    /// Expand in `ACTIONS` order
+    #[default]
+    Fixed,
+    /// Shuffle every layer deterministically for the seed
+    Seeded(u64),
+}
+
+/**
+When the search has reached its goal.
+*/
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum WinCondition {
+    /// All cells are zero (`Board::is_won`)
+    #[default]
+    AllZero,
+    /// The cells equal those of the target board, wherever its cursor is
+    Target(Board),
+}
+
+impl WinCondition {
+    pub fn is_met(&self, board: &Board) -> bool {
+        match self {
+            WinCondition::AllZero => board.is_won(),
+            WinCondition::Target(target) => board.same_cells(target),
+        }
+    }
+
+    /**
+    Whether no sequence of moves from `board` can meet the condition anymore.
+    */
+    fn is_dead_end(&self, board: &Board) -> bool {
+        match self {
+            WinCondition::AllZero => board.is_lost(),
+            WinCondition::Target(target) => board.misses_target(target),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SolverConfig {
+    child_order: ChildOrder,
+    wrap: bool,
+    visited_limit: Option<usize>,
+    win: WinCondition,
+    forbidden: Vec<Action>,
+    abort: Option<Arc<AtomicBool>>,
+    canonical_solution: bool,
+    free_pick: bool,
+    prefer_end_cursor: Option<Point>,
+    order_by_clears: bool,
+    layer_sizes: Option<Arc<Mutex<Vec<usize>>>>,
+}
+
+impl SolverConfig {
+    /**
+    The child order changes which of several optimal solutions is found, never their length.
+    */
+    pub fn child_order(mut self, child_order: ChildOrder) -> Self {
+        self.child_order = child_order;
+        self
+    }
+
+    /**
+    Expand the children clearing the most cells first, so the search stops at a win sooner.
+    Like the child order, this only changes which optimal solution is found.
+    */
+    pub fn order_by_clears(mut self, order: bool) -> Self {
+        self.order_by_clears = order;
+        self
+    }
+
+    /**
+    Play on a toroidal board (see `Board::wrapping_action`).
+    The solvers reject boards with walls then, since `Board::is_lost` would prune boards
+    which a move wrapping around the edge can still solve.
+    */
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /**
+    Whether a solution of a board turns into one of its symmetric variants by applying
+    the same symmetry, as needed to share it between them.
+    Target boards, forbidden actions, preferred end cursors and canonical solutions are
+    bound to the orientation of the board.
+    */
+    pub fn is_symmetric(&self) -> bool {
+        self.win == WinCondition::AllZero
+            && self.forbidden.is_empty()
+            && self.prefer_end_cursor.is_none()
+            && !self.canonical_solution
+    }
+
+    /**
+    Give up once more than `limit` boards were visited, independent of the machine's speed.
+    */
+    pub fn visited_limit(mut self, limit: Option<usize>) -> Self {
+        self.visited_limit = limit;
+        self
+    }
+
+    /**
+    Search for boards meeting `win` instead of cleared boards.
+    */
+    pub fn win_condition(mut self, win: WinCondition) -> Self {
+        self.win = win;
+        self
+    }
+
+    /**
+    Never play any of the `forbidden` actions.
+    */
+    pub fn forbid(mut self, forbidden: &[Action]) -> Self {
+        self.forbidden = forbidden.to_vec();
+        self
+    }
+
+    /**
+    Return the smallest of the solutions which are equal up to a symmetry of the board,
+    comparing actions in `ACTIONS` order.
+    */
+    pub fn canonical_solution(mut self, canonical: bool) -> Self {
+        self.canonical_solution = canonical;
+        self
+    }
+
+    /**
+    Among the shortest solutions return one ending with the cursor closest to `target`.
+    This expands the whole layer of the win instead of stopping at its first win.
+    */
+    pub fn prefer_end_cursor(mut self, target: Option<Point>) -> Self {
+        self.prefer_end_cursor = target;
+        self
+    }
+
+    /**
+    Let every move pick any cell as its origin instead of moving the cursor.
+    Only `solve_picks_with` honors this, since such solutions must tell the picked cells.
+    `solve_board_with` rejects such a config.
+    */
+    pub fn free_pick(mut self, free_pick: bool) -> Self {
+        self.free_pick = free_pick;
+        self
+    }
+
+    /**
+    Give up between two layers once `abort` is set, e.g. from a signal handler.
+    */
+    pub fn abort(mut self, abort: Option<Arc<AtomicBool>>) -> Self {
+        self.abort = abort;
+        self
+    }
+
+    /**
+    Append the number of new boards of every searched layer to `sizes`, starting with
+    the boards after one move. The layer of the win is expanded completely to count it.
+    */
+    pub fn record_layer_sizes(mut self, sizes: Option<Arc<Mutex<Vec<usize>>>>) -> Self {
+        self.layer_sizes = sizes;
+        self
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SolveOutcome {
+    Solved(Vec<Action>),
+    /// No solution within the move budget
+    Unsolvable,
+    /// The search gave up after reaching `SolverConfig::visited_limit`
+    LimitReached,
+    /// The search stopped after `SolverConfig::abort` was set
+    Aborted {
+        /// Moves of the deepest layer which was searched completely
+        depth: usize,
+        visited: usize,
+    },
+}
+
+impl SolveOutcome {
+    pub fn solution(self) -> Option<Vec<Action>> {
+        match self {
+            SolveOutcome::Solved(actions) => Some(actions),
+            _ => None,
+        }
+    }
+
+    /**
+    Persistent form of the outcome of a search with a budget of `max_moves`: the budget,
+    then 0 if unsolvable or 1 followed by the little endian `ActionSequence::bits` of the
+    solution. Other outcomes depend on the run and are not kept, so they give None.
+    */
+    pub fn to_bytes(&self, max_moves: usize) -> Option<Vec<u8>> {
+        let mut bytes = vec![u8::try_from(max_moves).ok()?];
+        match self {
+            SolveOutcome::Solved(actions) => {
+                bytes.push(1);
+                let seq: ActionSequence = actions.iter().copied().collect();
+                bytes.extend(seq.bits().to_le_bytes());
+            }
+            SolveOutcome::Unsolvable => bytes.push(0),
+            _ => return None,
+        }
+        Some(bytes)
+    }
+
+    /**
+    The outcome written by `to_bytes` as the answer for a budget of `max_moves`.
+    A shortest solution answers every budget, but no solution only budgets up to the
+    stored one. Gives None for a larger budget, which needs a new search, or malformed bytes.
+    */
+    pub fn from_bytes(bytes: &[u8], max_moves: usize) -> Option<Self> {
+        match bytes {
+            [stored, 0] => (max_moves <= *stored as usize).then_some(SolveOutcome::Unsolvable),
+            [_, 1, bits @ ..] => {
+                let seq = ActionSequence::from_bits(u64::from_le_bytes(bits.try_into().ok()?))?;
+                Some(if seq.length() <= max_moves {
+                    SolveOutcome::Solved(seq.into())
+                } else {
+                    SolveOutcome::Unsolvable
+                })
+            }
+            _ => None,
+        }
+    }
+}
 
 #[derive(Clone, Copy)]
 struct SolveStep {
@@ -12,44 +252,1678 @@ struct SolveStep {
 /**
 Perform a breadth-first search to find the shortest path of actions where `board.is_won()`.
 Besides pruning `board.is_lost()` this is a brute force search.
+
+The search never transforms the board, so the returned actions always apply
+to `board` in its own orientation (see `verify_solution`).
 */
 pub fn solve_board(board: &Board, max_moves: usize) -> Option<Vec<Action>> {
+    solve_board_with(board, max_moves, &SolverConfig::default()).solution()
+}
+
+pub fn solve_board_with(board: &Board, max_moves: usize, config: &SolverConfig) -> SolveOutcome {
     assert!(max_moves <= ActionSequence::MAX_LENGTH);
-    if board.is_won() {
-        return Some(vec![]);
+    assert!(!config.free_pick, "free_pick needs solve_picks_with");
+    assert_no_walls_with_wrap(board, config);
+    let start = vec![SolveStep {
+        board: *board,
+        seq: ActionSequence::new(),
+    }];
+    let first_only = config.prefer_end_cursor.is_none() && config.layer_sizes.is_none();
+    match search(
+        start,
+        max_moves,
+        config,
+        |b| config.win.is_met(b),
+        first_only,
+    ) {
+        Ok(won) => {
+            debug_assert!(won.iter().all(|s| s.seq.length() <= max_moves));
+            let step = match config.prefer_end_cursor {
+                Some(target) => won
+                    .iter()
+                    .min_by_key(|s| s.board.cursor().manhattan_distance(target))
+                    .unwrap(),
+                None => &won[0],
+            };
+            if config.canonical_solution {
+                SolveOutcome::Solved(canonicalize(board, step.seq.into(), config))
+            } else {
+                SolveOutcome::Solved(step.seq.into())
+            }
+        }
+        Err(outcome) => outcome,
     }
-    let mut steps = vec![SolveStep {
+}
+
+/**
+The smallest solution among `actions` transformed by every symmetry of `board`.
+*/
+fn canonicalize(board: &Board, actions: Vec<Action>, config: &SolverConfig) -> Vec<Action> {
+    Transform::ALL
+        .into_iter()
+        .filter(|t| board.symmetry(*t) == *board)
+        .map(|t| rotate_solution(&actions, t))
+        .filter(|variant| {
+            variant.iter().all(|a| !config.forbidden.contains(a))
+                && variant
+                    .iter()
+                    .try_fold(*board, |b, a| play(&b, *a, config.wrap))
+                    .is_some_and(|b| config.win.is_met(&b))
+        })
+        .min()
+        .unwrap_or(actions)
+}
+
+/**
+The number of moves of a shortest solution within `max_moves`, without building its actions.
+*/
+pub fn optimal_moves(board: &Board, max_moves: usize) -> Option<usize> {
+    assert!(max_moves <= ActionSequence::MAX_LENGTH);
+    let start = vec![SolveStep {
         board: *board,
         seq: ActionSequence::new(),
     }];
-    let mut moves_remaining = max_moves;
-    let mut visited: HashSet<Board> = HashSet::new();
-    while steps.len() > 0 && moves_remaining > 0 {
-        moves_remaining -= 1;
-        let mut next_steps: Vec<SolveStep> = Vec::with_capacity(steps.len() * ACTIONS.len());
-        next_steps.par_extend(
-            steps
-                .par_chunks(10000)
-                .flatten()
-                .copied()
-                .flat_map_iter(|step| {
-                    ACTIONS.into_iter().filter_map(move |action| {
-                        step.board.action(action).map(|board| SolveStep {
-                            board,
-                            seq: step.seq.add(action),
-                        })
-                    })
+    let config = SolverConfig::default();
+    search(start, max_moves, &config, Board::is_won, true)
+        .ok()
+        .map(|won| won[0].seq.length())
+}
+
+/**
+Find a shortest solution as the origin of every move followed by its direction.
+With `SolverConfig::free_pick` any cell can be the origin, i.e. the move is applied to
+the board with its cursor on that cell. Otherwise the origin is the cursor before the move.
+*/
+pub fn solve_picks_with(
+    board: &Board,
+    max_moves: usize,
+    config: &SolverConfig,
+) -> Option<Vec<(Point, Action)>> {
+    assert_no_walls_with_wrap(board, config);
+    if !config.free_pick {
+        let actions = solve_board_with(board, max_moves, config).solution()?;
+        let mut current = *board;
+        return Some(
+            actions
+                .into_iter()
+                .map(|action| {
+                    let origin = current.cursor();
+                    current = play(&current, action, config.wrap).unwrap();
+                    (origin, action)
                 })
-                .filter(|step| !visited.contains(&step.board) && !step.board.is_lost()),
+                .collect(),
         );
-
-        if let Some(solution) = next_steps.iter().find(|step| step.board.is_won()) {
-            return Some(solution.seq.into());
+    }
+    // The cursor is meaningless when picking, so boards are kept with the cursor at the first cell
+    let home = Point::from(0, 0);
+    let start = board.clone_with_cursor(home);
+    let mut parents: HashMap<Board, (Board, Point, Action)> = HashMap::new();
+    let mut layer = vec![start];
+    let mut won = config.win.is_met(&start).then_some(start);
+    for _ in 0..max_moves {
+        if won.is_some() || layer.is_empty() {
+            break;
+        }
+        let mut next_layer = vec![];
+        'layer: for parent in &layer {
+            for origin in (0..N).flat_map(Point::row_points) {
+                for action in ACTIONS.iter().filter(|a| !config.forbidden.contains(a)) {
+                    let Some(child) = play(&parent.clone_with_cursor(origin), *action, config.wrap)
+                    else {
+                        continue;
+                    };
+                    let child = child.clone_with_cursor(home);
+                    if child == start
+                        || parents.contains_key(&child)
+                        || config.win.is_dead_end(&child)
+                    {
+                        continue;
+                    }
+                    parents.insert(child, (*parent, origin, *action));
+                    if config.win.is_met(&child) {
+                        won = Some(child);
+                        break 'layer;
+                    }
+                    next_layer.push(child);
+                }
+            }
         }
+        layer = next_layer;
+    }
+    let mut current = won?;
+    let mut picks = vec![];
+    while let Some((parent, origin, action)) = parents.get(&current) {
+        picks.push((*origin, *action));
+        current = *parent;
+    }
+    picks.reverse();
+    Some(picks)
+}
+
+/**
+Find a shortest solution which never plays one of the `forbidden` actions.
+Every move is a single step of the cursor, so forbidding a direction also rules out
+walking the cursor across zero cells in that direction.
+*/
+pub fn solve_restricted(
+    board: &Board,
+    max_moves: usize,
+    forbidden: &[Action],
+) -> Option<Vec<Action>> {
+    let config = SolverConfig::default().forbid(forbidden);
+    solve_board_with(board, max_moves, &config).solution()
+}
+
+/**
+Breadth-first search from `steps` with at most `max_moves` further moves for the first
+layer containing boards where `goal` holds. Returns the steps of that layer reaching the goal,
+or with `first_only` just the first of them as soon as it is produced.
+*/
+fn search(
+    mut steps: Vec<SolveStep>,
+    max_moves: usize,
+    config: &SolverConfig,
+    goal: impl Fn(&Board) -> bool + Sync,
+    first_only: bool,
+) -> Result<Vec<SolveStep>, SolveOutcome> {
+    let reached = |steps: &[SolveStep]| -> Vec<SolveStep> {
+        steps.iter().filter(|s| goal(&s.board)).copied().collect()
+    };
+    let found = reached(&steps);
+    if !found.is_empty() {
+        return Ok(found);
+    }
+    let start_depth = steps.first().map_or(0, |s| s.seq.length());
+    let mut moves_remaining = max_moves;
+    let mut visited: HashSet<Board> = HashSet::new();
+    let mut rng = match config.child_order {
+        ChildOrder::Fixed => None,
+        ChildOrder::Seeded(seed) => Some(Rng::new(seed)),
+    };
+    while !steps.is_empty() && moves_remaining > 0 {
+        moves_remaining -= 1;
         for step in steps.iter() {
             visited.insert(step.board);
         }
+        if config
+            .visited_limit
+            .is_some_and(|limit| visited.len() > limit)
+        {
+            return Err(SolveOutcome::LimitReached);
+        }
+        if config
+            .abort
+            .as_ref()
+            .is_some_and(|abort| abort.load(Ordering::Relaxed))
+        {
+            return Err(SolveOutcome::Aborted {
+                depth: max_moves - moves_remaining - 1,
+                visited: visited.len(),
+            });
+        }
+        let next_steps = match rng.as_mut() {
+            // A shuffled layer decides which goal comes first only after expanding it
+            Some(rng) => {
+                let mut layer = next_layer(&steps, &visited, config);
+                rng.shuffle(&mut layer);
+                layer
+            }
+            None if first_only => match next_layer_until(&steps, &visited, config, &goal) {
+                Ok(layer) => layer,
+                Err(won) => {
+                    debug_assert_eq!(won.seq.length(), start_depth + max_moves - moves_remaining);
+                    return Ok(vec![won]);
+                }
+            },
+            None => next_layer(&steps, &visited, config),
+        };
+        if let Some(sizes) = &config.layer_sizes {
+            sizes.lock().unwrap().push(next_steps.len());
+        }
+
+        let found = reached(&next_steps);
+        if !found.is_empty() {
+            debug_assert!(found
+                .iter()
+                .all(|s| s.seq.length() == start_depth + max_moves - moves_remaining));
+            return Ok(found);
+        }
         steps = next_steps;
     }
+    Err(SolveOutcome::Unsolvable)
+}
+
+/**
+Default budget of moves: 20, or less if an `ActionSequence` cannot hold that many.
+*/
+pub const DEFAULT_MOVES: usize = if ActionSequence::MAX_LENGTH < 20 {
+    ActionSequence::MAX_LENGTH
+} else {
+    20
+};
+
+/**
+Number of boards `generate_hard_board` tries before giving up.
+*/
+pub const GENERATE_ATTEMPTS: usize = 100;
+
+/**
+Generate a board whose shortest solution takes exactly `target_moves` moves.
+Every attempt plays `target_moves` random moves backwards from a won board
+(see `Board::predecessors`) and is rejected if a shorter solution exists.
+None if all `GENERATE_ATTEMPTS` attempts fail, which gets likely for long targets
+where backward walks rarely avoid shortcuts.
+*/
+pub fn generate_hard_board(seed: u64, target_moves: usize) -> Option<Board> {
+    let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+    generate_from(&won, seed, target_moves)
+}
+
+/**
+Like `generate_hard_board`, but for a board with the given `walls`.
+*/
+#[cfg(feature = "walls")]
+pub fn generate_hard_board_with_walls(
+    seed: u64,
+    target_moves: usize,
+    walls: MarkBoard,
+) -> Option<Board> {
+    let empty: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+    let free = (0..N * N)
+        .map(|i| Point::from(i / N, i % N))
+        .find(|p| !walls.is_marked(*p))?;
+    let won = walls
+        .iter_marked()
+        .fold(empty.clone_with_cursor(free), |b, p| b.with_wall(p));
+    generate_from(&won, seed, target_moves)
+}
+
+fn generate_from(won: &Board, seed: u64, target_moves: usize) -> Option<Board> {
+    assert!(target_moves <= ActionSequence::MAX_LENGTH);
+    let mut rng = Rng::new(seed);
+    (0..GENERATE_ATTEMPTS).find_map(|_| {
+        let start = Point::from(rng.below(N), rng.below(N));
+        if won.is_wall(start) {
+            return None;
+        }
+        let mut board = won.clone_with_cursor(start);
+        for _ in 0..target_moves {
+            let priors = board.predecessors();
+            if priors.is_empty() {
+                return None;
+            }
+            board = priors[rng.below(priors.len())].1;
+        }
+        (optimal_moves(&board, target_moves) == Some(target_moves)).then_some(board)
+    })
+}
+
+/**
+Solve `board` such that the cell `first` becomes zero as early as possible: among all
+solutions which clear `first` at the earliest possible move, find a shortest one.
+This can be longer than an optimal solution, and fails if no such solution fits into `max_moves`.
+*/
+pub fn solve_with_priority(board: &Board, max_moves: usize, first: Point) -> Option<Vec<Action>> {
+    assert!(max_moves <= ActionSequence::MAX_LENGTH);
+    let config = SolverConfig::default();
+    let start = vec![SolveStep {
+        board: *board,
+        seq: ActionSequence::new(),
+    }];
+    let cleared = search(start, max_moves, &config, |b| b.cell(first) == 0, false).ok()?;
+    let depth = cleared[0].seq.length();
+    let won = search(cleared, max_moves - depth, &config, Board::is_won, true).ok()?;
+    Some(won[0].seq.into())
+}
+
+/**
+Solve `board` within `max_moves`, or else find the best board on the way: the non-lost board
+with the highest `Board::score` reached by the fewest moves, the first one of its layer on ties.
+Gives the solution if there is one, together with the won or best board and the moves to reach it.
+Every search looks for the first layer with a higher score than the best board so far.
+*/
+pub fn solve_or_best(board: &Board, max_moves: usize) -> (Option<Vec<Action>>, Board, Vec<Action>) {
+    assert!(max_moves <= ActionSequence::MAX_LENGTH);
+    let config = SolverConfig::default();
+    let start = SolveStep {
+        board: *board,
+        seq: ActionSequence::new(),
+    };
+    let mut best = start;
+    while !best.board.is_won() {
+        let target = best.board.score() + 1;
+        match search(
+            vec![start],
+            max_moves,
+            &config,
+            |b| b.score() >= target,
+            false,
+        ) {
+            Ok(layer) => {
+                best = layer
+                    .into_iter()
+                    .min_by_key(|s| Reverse(s.board.score()))
+                    .unwrap()
+            }
+            Err(_) => break,
+        }
+    }
+    let prefix: Vec<Action> = best.seq.into();
+    let solution = best.board.is_won().then(|| prefix.clone());
+    (solution, best.board, prefix)
+}
+
+/**
+Expand every step by one action. Boards which were already visited or are reached by
+several steps of the layer are kept only once, since all their sequences are equally short.
+*/
+fn next_layer(
+    steps: &[SolveStep],
+    visited: &HashSet<Board>,
+    config: &SolverConfig,
+) -> Vec<SolveStep> {
+    let mut next_steps: Vec<SolveStep> = Vec::with_capacity(steps.len() * ACTIONS.len());
+    next_steps.par_extend(
+        steps
+            .par_chunks(10000)
+            .flatten()
+            .copied()
+            .flat_map_iter(|step| expand(step, visited, config)),
+    );
+    dedupe(next_steps)
+}
+
+/**
+Like `next_layer`, but stop expanding at the first child where `goal` holds.
+The layer is split into chunks which search in parallel. A chunk gives up once an
+earlier chunk found the goal, so the child found is always the first one `next_layer`
+would produce.
+*/
+fn next_layer_until(
+    steps: &[SolveStep],
+    visited: &HashSet<Board>,
+    config: &SolverConfig,
+    goal: &(impl Fn(&Board) -> bool + Sync),
+) -> Result<Vec<SolveStep>, SolveStep> {
+    let found_chunk = AtomicUsize::new(usize::MAX);
+    let chunks: Vec<Result<Vec<SolveStep>, SolveStep>> = steps
+        .par_chunks(1024)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut children = Vec::with_capacity(chunk.len() * ACTIONS.len());
+            for step in chunk {
+                if found_chunk.load(Ordering::Relaxed) < i {
+                    break;
+                }
+                for child in expand(*step, visited, config) {
+                    if goal(&child.board) {
+                        found_chunk.fetch_min(i, Ordering::Relaxed);
+                        return Err(child);
+                    }
+                    children.push(child);
+                }
+            }
+            Ok(children)
+        })
+        .collect();
+    let mut next_steps = Vec::with_capacity(steps.len() * ACTIONS.len());
+    for chunk in chunks {
+        next_steps.extend(chunk?);
+    }
+    Ok(dedupe(next_steps))
+}
+
+/**
+The children of `step` which were not visited and are no dead end,
+with the most zero cells first for `SolverConfig::order_by_clears`.
+*/
+fn expand<'a>(
+    step: SolveStep,
+    visited: &'a HashSet<Board>,
+    config: &'a SolverConfig,
+) -> impl Iterator<Item = SolveStep> + 'a {
+    let wrap = config.wrap;
+    let children = ACTIONS
+        .into_iter()
+        .filter(|action| !config.forbidden.contains(action))
+        .filter_map(move |action| {
+            let child = play(&step.board, action, wrap).map(|board| SolveStep {
+                board,
+                seq: step.seq.add(action),
+            });
+            if let Some(c) = &child {
+                debug_assert!(is_valid_expansion(&step, c, wrap));
+            }
+            child
+        })
+        .filter(|child| !visited.contains(&child.board) && !config.win.is_dead_end(&child.board));
+    if config.order_by_clears {
+        // Stable, so equally good children keep the `ACTIONS` order
+        Either::Right(
+            children
+                .sorted_by_key(|child| Reverse(child.board.score()))
+                .collect_vec()
+                .into_iter(),
+        )
+    } else {
+        Either::Left(children)
+    }
+}
+
+fn dedupe(mut steps: Vec<SolveStep>) -> Vec<SolveStep> {
+    let mut layer_boards: HashSet<Board> = HashSet::with_capacity(steps.len());
+    steps.retain(|step| layer_boards.insert(step.board));
+    steps
+}
+
+fn assert_no_walls_with_wrap(board: &Board, config: &SolverConfig) {
+    assert!(
+        !config.wrap || board.walls().count() == 0,
+        "wrap needs a board without walls"
+    );
+}
+
+fn play(board: &Board, action: Action, wrap: bool) -> Option<Board> {
+    if wrap {
+        board.wrapping_action(action)
+    } else {
+        board.action(action)
+    }
+}
+
+/**
+Check that `child` extends the sequence of `parent` by one action
+and that replaying this action on the parent board gives the child board.
+*/
+fn is_valid_expansion(parent: &SolveStep, child: &SolveStep, wrap: bool) -> bool {
+    let len = parent.seq.length();
+    child.seq.length() == len + 1
+        && (0..len).all(|i| child.seq.get(i) == parent.seq.get(i))
+        && play(&parent.board, child.seq.get(len), wrap) == Some(child.board)
+}
+
+/**
+Count the distinct shortest solutions of `board` within `max_moves`, saturating at `u64::MAX`.
+Every board on a shortest solution is first reached at its depth, so the counts of
+the paths to each board of a layer only flow into boards of the next layer.
+*/
+pub fn count_shortest_solutions(board: &Board, max_moves: usize) -> Option<u64> {
+    let mut visited: HashSet<Board> = HashSet::from([*board]);
+    let mut layer: HashMap<Board, u64> = HashMap::from([(*board, 1)]);
+    for depth in 0..=max_moves {
+        let won: u64 = layer
+            .iter()
+            .filter(|(b, _)| b.is_won())
+            .fold(0, |acc, (_, n)| acc.saturating_add(*n));
+        if won > 0 {
+            return Some(won);
+        }
+        if depth == max_moves || layer.is_empty() {
+            break;
+        }
+        let mut next: HashMap<Board, u64> = HashMap::new();
+        for (b, n) in &layer {
+            for child in b.children().filter(|c| !visited.contains(c)) {
+                let count = next.entry(child).or_insert(0);
+                *count = count.saturating_add(*n);
+            }
+        }
+        visited.extend(next.keys());
+        layer = next;
+    }
     None
 }
+
+/**
+The shortest solution of `board` within `max_moves` at `index` when all of them are
+ordered by their actions in `ACTIONS` order, so index 0 is the one `solve_board` finds.
+None if the board has no solution or at most `index` shortest solutions.
+*/
+pub fn nth_shortest_solution(board: &Board, max_moves: usize, index: u64) -> Option<Vec<Action>> {
+    let moves = solve_board(board, max_moves)?.len();
+    let mut index = index;
+    let mut current = *board;
+    let mut actions = Vec::with_capacity(moves);
+    for remaining in (0..moves).rev() {
+        // Skip all solutions starting with a smaller action
+        let (action, next) = ACTIONS.into_iter().find_map(|a| {
+            let next = current.action(a).filter(|b| !b.is_lost())?;
+            let count = count_shortest_solutions(&next, remaining)?;
+            if index < count {
+                Some((a, next))
+            } else {
+                index -= count;
+                None
+            }
+        })?;
+        actions.push(action);
+        current = next;
+    }
+    // A won board has only the empty solution
+    (index == 0).then_some(actions)
+}
+
+/**
+Check whether `action` starts some shortest solution of `board` within `max_moves`,
+i.e. the board after it is exactly one move closer to the win.
+*/
+pub fn is_optimal_move(board: &Board, action: Action, max_moves: usize) -> bool {
+    let Some(optimum) = solve_board(board, max_moves).map(|s| s.len()) else {
+        return false;
+    };
+    match board.action(action) {
+        Some(next) if optimum > 0 => {
+            solve_board(&next, optimum - 1).is_some_and(|s| s.len() == optimum - 1)
+        }
+        _ => false,
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MoveKind {
+    /// The move cleared at least one cell
+    Clearing,
+    /// The move did not clear any cell, it only changed cells or moved the cursor
+    Reposition,
+}
+
+/**
+Solve `board` and tell for every action of the solution whether it cleared any cell.
+*/
+pub fn solve_board_classified(board: &Board, max_moves: usize) -> Option<Vec<(Action, MoveKind)>> {
+    let solution = solve_board(board, max_moves)?;
+    let mut current = *board;
+    Some(
+        solution
+            .into_iter()
+            .map(|action| {
+                let (next, clears) = current.action_with_clears(action).unwrap();
+                current = next;
+                let kind = if clears > 0 {
+                    MoveKind::Clearing
+                } else {
+                    MoveKind::Reposition
+                };
+                (action, kind)
+            })
+            .collect(),
+    )
+}
+
+/**
+A move of a solution, or a walk of several moves which only move the cursor.
+*/
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Step {
+    Move(Action),
+    /// Walk the cursor to the point without changing any cell
+    GoTo(Point),
+}
+
+/**
+Replace every run of at least two consecutive moves of `actions` which leave all cells
+unchanged by a `Step::GoTo` to where the run ends. Other moves are kept as they are.
+*/
+pub fn collapse_walks(board: &Board, actions: &[Action]) -> Vec<Step> {
+    let mut steps = vec![];
+    let mut walk: Vec<Action> = vec![];
+    let mut current = *board;
+    let end_walk = |walk: &mut Vec<Action>, steps: &mut Vec<Step>, cursor: Point| {
+        match walk.len() {
+            0 => {}
+            1 => steps.push(Step::Move(walk[0])),
+            _ => steps.push(Step::GoTo(cursor)),
+        }
+        walk.clear();
+    };
+    for action in actions {
+        let next = current.action(*action).expect("should apply solution");
+        if next.same_cells(&current) {
+            walk.push(*action);
+        } else {
+            end_walk(&mut walk, &mut steps, current.cursor());
+            steps.push(Step::Move(*action));
+        }
+        current = next;
+    }
+    end_walk(&mut walk, &mut steps, current.cursor());
+    steps
+}
+
+/**
+Solve `board` and pair every action of the solution with the board after it.
+*/
+pub fn solve_board_trace(board: &Board, max_moves: usize) -> Option<Vec<(Action, Board)>> {
+    let solution = solve_board(board, max_moves)?;
+    let mut current = *board;
+    Some(
+        solution
+            .into_iter()
+            .map(|action| {
+                current = current.action(action).unwrap();
+                (action, current)
+            })
+            .collect(),
+    )
+}
+
+/**
+Solve `board` for every cursor start off the walls and return the start with the shortest
+solution. Ties are broken by the lowest cell index.
+*/
+pub fn solve_best_start(board: &Board, max_moves: usize) -> Option<(Point, Vec<Action>)> {
+    let mut best: Option<(Point, Vec<Action>)> = None;
+    for p in (0..N * N)
+        .map(|i| Point::from(i / N, i % N))
+        .filter(|p| !board.is_wall(*p))
+    {
+        // Only a strictly shorter solution can replace the best one
+        let budget = match &best {
+            Some((_, solution)) if solution.is_empty() => break,
+            Some((_, solution)) => solution.len() - 1,
+            None => max_moves,
+        };
+        if let Some(solution) = solve_board(&board.clone_with_cursor(p), budget) {
+            best = Some((p, solution));
+        }
+    }
+    best
+}
+
+/**
+Count the nodes of the game tree below `board` up to `depth` moves, where every
+path counts separately. Lost boards are pruned and won boards have no children.
+Comparing this with `reachable_within` shows how much the visited set saves.
+*/
+pub fn game_tree_size(board: &Board, depth: usize) -> u64 {
+    if depth == 0 || board.is_won() {
+        return 0;
+    }
+    board
+        .children()
+        .map(|child| 1 + game_tree_size(&child, depth - 1))
+        .sum()
+}
+
+/**
+Count the distinct boards other than `board` reachable within `depth` moves,
+pruned like `game_tree_size`.
+*/
+pub fn reachable_within(board: &Board, depth: usize) -> usize {
+    reachable_layers(board, depth).iter().map(Vec::len).sum()
+}
+
+/**
+The greatest distance from `board` to any board reachable within `max_depth` moves,
+i.e. its eccentricity in the graph of non-lost boards capped at `max_depth`.
+Won boards end the game, so like lost boards they are never expanded.
+*/
+pub fn component_diameter(board: &Board, max_depth: usize) -> usize {
+    reachable_layers(board, max_depth).len()
+}
+
+/**
+The boards first reached at each distance from 1 up to `depth`, without trailing empty layers.
+*/
+fn reachable_layers(board: &Board, depth: usize) -> Vec<Vec<Board>> {
+    let start = [*board];
+    let mut visited: HashSet<Board> = HashSet::from(start);
+    let mut layers: Vec<Vec<Board>> = vec![];
+    while layers.len() < depth {
+        let layer: Vec<Board> = layers
+            .last()
+            .map_or(&start[..], Vec::as_slice)
+            .iter()
+            .filter(|b| !b.is_won())
+            .flat_map(Board::children)
+            .filter(|b| visited.insert(*b))
+            .collect();
+        if layer.is_empty() {
+            break;
+        }
+        layers.push(layer);
+    }
+    layers
+}
+
+/**
+Every cell whose value changes at some move of `actions`, replayed from `board`.
+Replaying stops at the first action which leaves the grid.
+*/
+pub fn solution_footprint(board: &Board, actions: &[Action]) -> HashSet<Point> {
+    let mut footprint = HashSet::new();
+    let mut current = *board;
+    for action in actions {
+        let Some(next) = current.action(*action) else {
+            break;
+        };
+        footprint.extend(current.diff(&next).into_iter().map(|(p, _, _)| p));
+        current = next;
+    }
+    footprint
+}
+
+/**
+Check that applying `actions` one after another to `board` wins the game.
+*/
+pub fn verify_solution(board: &Board, actions: &[Action]) -> bool {
+    actions
+        .iter()
+        .try_fold(*board, |b, a| b.action(*a))
+        .is_some_and(|b| b.is_won())
+}
+
+/**
+The solution of `board.symmetry(t)` for a solution `actions` of `board`.
+*/
+pub fn rotate_solution(actions: &[Action], t: Transform) -> Vec<Action> {
+    actions.iter().map(|a| a.symmetry(t)).collect()
+}
+
+/**
+The solution of `board` for a solution `actions` of `board.symmetry(t)`,
+e.g. to orient a solution of the canonical board like the original.
+*/
+pub fn reverse_rotate_solution(actions: &[Action], t: Transform) -> Vec<Action> {
+    actions.iter().map(|a| a.reverse_symmetry(t)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::{Iddfs, Solve};
+    use crate::test_util::SAMPLE_MOVES;
+    use crate::values::Transform;
+
+    #[test]
+    fn validate_expansion() {
+        let board: Board = "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let parent = SolveStep {
+            board,
+            seq: ActionSequence::new(),
+        };
+        let child = SolveStep {
+            board: board.action(Action::DOWN).unwrap(),
+            seq: parent.seq.add(Action::DOWN),
+        };
+        assert!(is_valid_expansion(&parent, &child, false));
+        let wrong_action = SolveStep {
+            seq: parent.seq.add(Action::RIGHT),
+            ..child
+        };
+        assert!(!is_valid_expansion(&parent, &wrong_action, false));
+        let wrong_board = SolveStep {
+            board: board.action(Action::RIGHT).unwrap(),
+            ..child
+        };
+        assert!(!is_valid_expansion(&parent, &wrong_board, false));
+        let skipped = SolveStep {
+            seq: child.seq.add(Action::DOWN),
+            ..child
+        };
+        assert!(!is_valid_expansion(&parent, &skipped, false));
+    }
+
+    #[test]
+    fn solve_mirrored_board() {
+        let board: Board = "0 0 6 0|0 9 3 0|0 9 18 3|0 0 15 0".parse().unwrap();
+        let mirror = board.symmetry(Transform::Mirror);
+        let solution = solve_board(&board, DEFAULT_MOVES).expect("should solve board");
+        let mirror_solution = solve_board(&mirror, DEFAULT_MOVES).expect("should solve mirror");
+        assert!(verify_solution(&board, &solution));
+        assert!(verify_solution(&mirror, &mirror_solution));
+        assert_eq!(solution.len(), mirror_solution.len());
+        let mirrored: Vec<Action> = solution
+            .iter()
+            .map(|a| a.symmetry(Transform::Mirror))
+            .collect();
+        assert!(
+            verify_solution(&mirror, &mirrored),
+            "should solve mirror with mirrored actions"
+        );
+        assert!(!verify_solution(&board, &solution[1..]));
+    }
+
+    #[test]
+    fn stop_at_first_win() {
+        let board: Board = "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(
+            solve_board(&board, DEFAULT_MOVES),
+            Some(vec![Action::RIGHT])
+        );
+
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        let config = SolverConfig::default();
+        let goal = |b: &Board| b.cell(Point::from(1, 2)) == 0;
+        let mut visited = HashSet::new();
+        let mut steps = vec![SolveStep {
+            board,
+            seq: ActionSequence::new(),
+        }];
+        for _ in 0..6 {
+            visited.extend(steps.iter().map(|step| step.board));
+            let layer = next_layer(&steps, &visited, &config);
+            let first = layer.iter().find(|step| goal(&step.board));
+            match next_layer_until(&steps, &visited, &config, &goal) {
+                Ok(until) => {
+                    assert!(first.is_none());
+                    assert_eq!(until.len(), layer.len());
+                }
+                Err(won) => {
+                    assert_eq!(Some(won.seq), first.map(|step| step.seq));
+                    return;
+                }
+            }
+            steps = layer;
+        }
+        panic!("should reach goal");
+    }
+
+    #[test]
+    fn dedupe_layers() {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        let mut visited = HashSet::new();
+        let mut steps = vec![SolveStep {
+            board,
+            seq: ActionSequence::new(),
+        }];
+        let mut expanded = 0;
+        for _ in 0..8 {
+            expanded += steps.len() * ACTIONS.len();
+            visited.extend(steps.iter().map(|step| step.board));
+            steps = next_layer(&steps, &visited, &SolverConfig::default());
+            let distinct: HashSet<Board> = steps.iter().map(|step| step.board).collect();
+            assert_eq!(distinct.len(), steps.len(), "should not repeat boards");
+        }
+        assert!(visited.len() < expanded, "should expand fewer boards");
+        assert_eq!(
+            solve_board(&board, DEFAULT_MOVES).map(|s| s.len()),
+            Some(SAMPLE_MOVES)
+        );
+    }
+
+    #[test]
+    fn admissible_heuristic() {
+        let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(won.heuristic(), 0);
+        let diagonal: Board = "3 0 0 0|0 3 0 0|0 0 3 0|0 0 0 3".parse().unwrap();
+        // A single line with diagonal moves
+        let lines = if cfg!(feature = "diagonals") { 1 } else { 4 };
+        assert_eq!(diagonal.heuristic(), lines);
+        for board_str in [
+            "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0",
+            "0 0 6 0|0 9 3 0|0 9 18 3|0 0 15 0",
+            "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0",
+        ] {
+            let mut board: Board = board_str.parse().unwrap();
+            let solution = solve_board(&board, DEFAULT_MOVES).unwrap();
+            // Every board along an optimal solution is solvable in the remaining moves
+            for (i, action) in solution.iter().enumerate() {
+                assert!(
+                    board.heuristic() <= solution.len() - i,
+                    "should not overestimate {board}"
+                );
+                board = board.action(*action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn trace_solution() {
+        let board: Board = "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let trace = solve_board_trace(&board, DEFAULT_MOVES).unwrap();
+        let solution = solve_board(&board, DEFAULT_MOVES).unwrap();
+        assert_eq!(trace.len(), solution.len());
+        assert!(trace.last().unwrap().1.is_won());
+        assert_eq!(trace[0], (solution[0], board.action(solution[0]).unwrap()));
+        assert_eq!(solve_board_trace(&board, 1), None);
+    }
+
+    #[test]
+    fn classify_moves() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let classified = solve_board_classified(&board, DEFAULT_MOVES).unwrap();
+        let clearing = classified
+            .iter()
+            .filter(|(_, kind)| *kind == MoveKind::Clearing)
+            .count();
+        let reposition = classified
+            .iter()
+            .filter(|(_, kind)| *kind == MoveKind::Reposition)
+            .count();
+        assert_eq!((clearing, reposition), (1, 3));
+        assert_eq!(
+            clearing + reposition,
+            solve_board(&board, DEFAULT_MOVES).unwrap().len()
+        );
+        assert_eq!(
+            classified.last(),
+            Some(&(Action::RIGHT, MoveKind::Clearing))
+        );
+    }
+
+    #[test]
+    fn collapse_cursor_walks() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let solution = solve_board(&board, 10).unwrap();
+        assert_eq!(
+            collapse_walks(&board, &solution),
+            vec![Step::GoTo(Point::from(3, 0)), Step::Move(Action::RIGHT)]
+        );
+        let single = board.clone_with_cursor(Point::from(2, 0));
+        assert_eq!(
+            collapse_walks(&single, &[Action::DOWN, Action::RIGHT]),
+            vec![Step::Move(Action::DOWN), Step::Move(Action::RIGHT)]
+        );
+    }
+
+    #[test]
+    fn find_best_start() {
+        let board: Board = "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let default = solve_board(&board, DEFAULT_MOVES).unwrap();
+        let (start, solution) = solve_best_start(&board, DEFAULT_MOVES).unwrap();
+        assert!(solution.len() <= default.len());
+        assert_eq!(start, Point::from(1, 1));
+        assert_eq!(solution, vec![Action::RIGHT]);
+        let grid = board.to_grid();
+        assert!(verify_solution(&Board::from_grid(grid, start), &solution));
+        assert_eq!(solve_best_start(&board, 0), None);
+    }
+
+    #[test]
+    fn solve_wrapped_board() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 0 0 3".parse().unwrap();
+        let bounded = solve_board(&board, DEFAULT_MOVES).unwrap();
+        let config = SolverConfig::default().wrap(true);
+        let wrapped = solve_board_with(&board, DEFAULT_MOVES, &config)
+            .solution()
+            .unwrap();
+        assert_eq!(bounded.len(), 4);
+        assert_eq!(wrapped, vec![Action::UP, Action::LEFT]);
+        assert!(!verify_solution(&board, &wrapped));
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    #[should_panic(expected = "wrap needs a board without walls")]
+    fn reject_walls_with_wrap() {
+        let board: Board = "3 W 3 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert!(
+            board.wrapping_action(Action::LEFT).unwrap().is_won(),
+            "should clear both 3s around the edge"
+        );
+        assert!(board.is_lost(), "should only look up to the edges");
+        solve_board_with(&board, 4, &SolverConfig::default().wrap(true));
+    }
+
+    #[test]
+    fn solve_towards_target() {
+        let board: Board = "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 5".parse().unwrap();
+        let target: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 5".parse().unwrap();
+        assert_eq!(solve_board(&board, 10), None, "should keep dead cell");
+        let config = SolverConfig::default().win_condition(WinCondition::Target(target));
+        let solution = solve_board_with(&board, 10, &config).solution().unwrap();
+        // The cursor reaches the 4s in one move instead of two with diagonal moves
+        let moves = if cfg!(feature = "diagonals") { 2 } else { 3 };
+        assert_eq!(solution.len(), moves);
+        let end = solution
+            .iter()
+            .try_fold(board, |b, a| b.action(*a))
+            .unwrap();
+        assert!(end.same_cells(&target));
+        let refilled: Board = "0 0 0 0|0 4 4 0|0 0 1 0|0 0 0 5".parse().unwrap();
+        let config = SolverConfig::default().win_condition(WinCondition::Target(refilled));
+        assert_eq!(
+            solve_board_with(&board, 10, &config),
+            SolveOutcome::Unsolvable,
+            "should not refill a cleared cell"
+        );
+    }
+
+    #[test]
+    fn solve_free_pick() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let cursor = solve_picks_with(&board, 10, &SolverConfig::default()).unwrap();
+        assert_eq!(cursor.len(), 4);
+        assert_eq!(cursor[3], (Point::from(3, 0), Action::RIGHT));
+        let config = SolverConfig::default().free_pick(true);
+        let picks = solve_picks_with(&board, 10, &config).unwrap();
+        assert_eq!(picks, vec![(Point::from(3, 0), Action::RIGHT)]);
+
+        let board: Board = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse().unwrap();
+        let cursor = solve_board(&board, 10).unwrap();
+        let picks = solve_picks_with(&board, 10, &config).unwrap();
+        assert!(picks.len() < cursor.len());
+        let end = picks.iter().try_fold(board, |b, (origin, action)| {
+            b.clone_with_cursor(*origin).action(*action)
+        });
+        assert!(end.unwrap().is_won());
+    }
+
+    #[test]
+    #[should_panic(expected = "free_pick needs solve_picks_with")]
+    fn reject_free_pick() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        solve_board_with(&board, 10, &SolverConfig::default().free_pick(true));
+    }
+
+    #[test]
+    fn forbid_actions() {
+        let board: Board = "0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0".parse().unwrap();
+        assert!(solve_board(&board, 10).unwrap().contains(&Action::UP));
+        let solution = solve_restricted(&board, 10, &[Action::UP]).unwrap();
+        assert!(!solution.contains(&Action::UP), "{solution:?}");
+        assert!(verify_solution(&board, &solution));
+        #[cfg(not(feature = "diagonals"))]
+        let downwards = [Action::DOWN];
+        #[cfg(feature = "diagonals")]
+        let downwards = [Action::DOWN, Action::DOWNLEFT, Action::DOWNRIGHT];
+        assert_eq!(solve_restricted(&board, 10, &downwards), None);
+    }
+
+    #[test]
+    fn canonical_symmetric_solution() {
+        let board: Board = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(board.transpose(), board);
+        let config = SolverConfig::default().canonical_solution(true);
+        let solution = solve_board_with(&board, 10, &config).solution().unwrap();
+        assert!(verify_solution(&board, &solution));
+        let transposed: Vec<Action> = solution
+            .iter()
+            .map(|a| a.symmetry(Transform::MirrorDeg270))
+            .collect();
+        assert!(verify_solution(&board, &transposed));
+        assert!(solution <= transposed, "should be minimal");
+        assert_eq!(
+            canonicalize(&board, transposed, &config),
+            solution,
+            "should be stable"
+        );
+    }
+
+    #[test]
+    fn abort_search() {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        let abort = Arc::new(AtomicBool::new(false));
+        let sizes = Arc::new(Mutex::new(vec![]));
+        let config = SolverConfig::default()
+            .abort(Some(abort.clone()))
+            .record_layer_sizes(Some(sizes.clone()));
+        let outcome = std::thread::scope(|s| {
+            // Set the flag during the search like a signal handler would
+            s.spawn(|| {
+                while sizes.lock().unwrap().len() < 3 {
+                    std::thread::yield_now();
+                }
+                abort.store(true, Ordering::Relaxed);
+            });
+            solve_board_with(&board, DEFAULT_MOVES, &config)
+        });
+        match outcome {
+            SolveOutcome::Aborted { depth, visited } => {
+                assert!((3..SAMPLE_MOVES).contains(&depth), "{depth}");
+                assert!(visited > depth, "{visited}");
+            }
+            outcome => panic!("should abort: {outcome:?}"),
+        }
+        abort.store(false, Ordering::Relaxed);
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        assert_eq!(
+            solve_board_with(&board, DEFAULT_MOVES, &config)
+                .solution()
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn limit_visited_boards() {
+        // The sample board needs millions of boards with diagonal moves
+        let (board, moves): (Board, _) = if cfg!(feature = "diagonals") {
+            ("0 0 6 0|0 9 3 0|0 9 18 3|0 0 15 0".parse().unwrap(), 9)
+        } else {
+            ("18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap(), 13)
+        };
+        let tight = SolverConfig::default().visited_limit(Some(100));
+        assert_eq!(
+            solve_board_with(&board, DEFAULT_MOVES, &tight),
+            SolveOutcome::LimitReached
+        );
+        let generous = SolverConfig::default().visited_limit(Some(1_000_000));
+        let outcome = solve_board_with(&board, DEFAULT_MOVES, &generous);
+        assert_eq!(outcome.solution().map(|s| s.len()), Some(moves));
+        let unsolvable: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        assert_eq!(
+            solve_board_with(&unsolvable, 3, &tight),
+            SolveOutcome::Unsolvable
+        );
+    }
+
+    #[test]
+    fn prioritize_cell() {
+        let board: Board = "0 2 0 0|0 2 0 0|0 0 0 0|0 1 1 0".parse().unwrap();
+        let first = Point::from(3, 1);
+        let cleared_at = |actions: &[Action]| {
+            let mut b = board;
+            actions
+                .iter()
+                .position(|a| {
+                    b = b.action(*a).unwrap();
+                    b.cell(first) == 0
+                })
+                .unwrap()
+        };
+        let optimal = solve_board(&board, DEFAULT_MOVES).unwrap();
+        let prioritized = solve_with_priority(&board, DEFAULT_MOVES, first).unwrap();
+        assert!(verify_solution(&board, &prioritized));
+        let moves = if cfg!(feature = "diagonals") {
+            (6, 7)
+        } else {
+            (7, 9)
+        };
+        assert_eq!((optimal.len(), prioritized.len()), moves);
+        assert!(cleared_at(&prioritized) < cleared_at(&optimal));
+        assert_eq!(solve_with_priority(&board, moves.1 - 1, first), None);
+    }
+
+    #[test]
+    fn count_optimal_moves() {
+        for board_str in [
+            "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0",
+            "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0",
+            "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0",
+            "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0",
+            "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0",
+        ] {
+            let board: Board = board_str.parse().unwrap();
+            for max_moves in [3, 10] {
+                assert_eq!(
+                    optimal_moves(&board, max_moves),
+                    solve_board(&board, max_moves).map(|s| s.len()),
+                    "should match solution of {board}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "diagonals"))]
+    fn prefer_end_cursor() {
+        let board: Board = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse().unwrap();
+        let end_cursor = |solution: &[Action]| {
+            solution
+                .iter()
+                .try_fold(board, |b, a| b.action(*a))
+                .unwrap()
+                .cursor()
+        };
+        let ends: HashSet<Point> = [Point::from(0, 2), Point::from(2, 0)].into();
+        for (target, end) in [
+            (Point::from(0, 3), Point::from(0, 2)),
+            (Point::from(3, 0), Point::from(2, 0)),
+        ] {
+            let config = SolverConfig::default().prefer_end_cursor(Some(target));
+            let solution = solve_board_with(&board, 10, &config).solution().unwrap();
+            assert_eq!(solution.len(), 6);
+            assert_eq!(end_cursor(&solution), end);
+        }
+        let default = solve_board(&board, 10).unwrap();
+        assert!(ends.contains(&end_cursor(&default)));
+    }
+
+    #[test]
+    #[cfg(feature = "diagonals")]
+    fn prefer_end_cursor_diagonally() {
+        // The mirrored solutions of the symmetric board end on the same cell here
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        for (target, end) in [
+            (Point::from(3, 0), Point::from(3, 0)),
+            (Point::from(3, 3), Point::from(3, 1)),
+        ] {
+            let config = SolverConfig::default().prefer_end_cursor(Some(target));
+            let solution = solve_board_with(&board, 10, &config).solution().unwrap();
+            assert_eq!(solution.len(), 4);
+            let last = solution.iter().try_fold(board, |b, a| b.action(*a));
+            assert_eq!(last.unwrap().cursor(), end);
+        }
+    }
+
+    #[test]
+    fn trace_footprint() {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        let solution = solve_board(&board, DEFAULT_MOVES).unwrap();
+        let footprint = solution_footprint(&board, &solution);
+        let nonzero: HashSet<Point> = (0..N)
+            .flat_map(Point::row_points)
+            .filter(|p| board.cell(*p) != 0)
+            .collect();
+        assert!(footprint.is_superset(&nonzero), "should clear every cell");
+        assert!(
+            footprint.iter().all(|p| nonzero.contains(p)),
+            "zeros stay zero"
+        );
+        assert!(solution_footprint(&board, &[]).is_empty());
+    }
+
+    #[test]
+    fn count_solutions() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        // Diagonal moves walk to the 3s in several ways
+        let count = if cfg!(feature = "diagonals") { 9 } else { 1 };
+        assert_eq!(count_shortest_solutions(&board, 10), Some(count));
+        assert_eq!(count_shortest_solutions(&board, 3), None);
+        let symmetric: Board = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse().unwrap();
+        let count = count_shortest_solutions(&symmetric, 10).unwrap();
+        assert!(
+            count >= 2 && count.is_multiple_of(2),
+            "should count mirrored solutions: {count}"
+        );
+        let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(count_shortest_solutions(&won, 0), Some(1));
+    }
+
+    #[test]
+    fn pick_nth_solution() {
+        let board: Board = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse().unwrap();
+        let count = count_shortest_solutions(&board, 10).unwrap();
+        let solutions: Vec<Vec<Action>> = (0..count)
+            .map(|i| nth_shortest_solution(&board, 10, i).unwrap())
+            .collect();
+        assert_eq!(solutions[0], solve_board(&board, 10).unwrap());
+        assert!(solutions.iter().all(|s| verify_solution(&board, s)));
+        assert!(solutions.iter().all(|s| s.len() == solutions[0].len()));
+        assert!(
+            solutions.windows(2).all(|w| w[0] < w[1]),
+            "should be distinct and ordered"
+        );
+        assert_eq!(nth_shortest_solution(&board, 10, count), None);
+        assert_eq!(nth_shortest_solution(&board, 2, 0), None);
+        let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(nth_shortest_solution(&won, 0, 0), Some(vec![]));
+        assert_eq!(nth_shortest_solution(&won, 0, 1), None);
+    }
+
+    #[test]
+    fn check_optimal_move() {
+        let grid = [[0, 0, 0, 0], [0, 4, 4, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
+        let board = Board::from_grid(grid, Point::from(1, 0));
+        assert_eq!(solve_board(&board, DEFAULT_MOVES).map(|s| s.len()), Some(2));
+        assert!(is_optimal_move(&board, Action::RIGHT, DEFAULT_MOVES));
+        assert!(
+            !is_optimal_move(&board, Action::UP, DEFAULT_MOVES),
+            "should be wasteful"
+        );
+        assert!(!is_optimal_move(&board, Action::DOWN, DEFAULT_MOVES));
+        assert!(
+            !is_optimal_move(&board, Action::LEFT, DEFAULT_MOVES),
+            "should be off-grid"
+        );
+        assert!(
+            !is_optimal_move(&board, Action::RIGHT, 1),
+            "should exceed budget"
+        );
+    }
+
+    #[test]
+    fn count_game_tree() {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        assert_eq!(game_tree_size(&board, 0), 0);
+        assert_eq!(game_tree_size(&board, 1), board.children().count() as u64);
+        assert_eq!(
+            game_tree_size(&board, 1),
+            reachable_within(&board, 1) as u64
+        );
+        let tree = game_tree_size(&board, 8);
+        let reachable = reachable_within(&board, 8);
+        let expected = if cfg!(feature = "diagonals") {
+            (870_932, 181_549)
+        } else {
+            (9_067, 3_776)
+        };
+        assert_eq!((tree, reachable), expected);
+        assert!(tree > reachable as u64, "should count duplicates");
+    }
+
+    #[test]
+    fn seeded_child_order() {
+        // With diagonal moves, this board has a single shortest solution
+        let board: Board = if cfg!(feature = "diagonals") {
+            "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0".parse().unwrap()
+        } else {
+            "0 0 6 0|0 9 3 0|0 9 18 3|0 0 15 0".parse().unwrap()
+        };
+        let seeded = |seed| {
+            let config = SolverConfig::default().child_order(ChildOrder::Seeded(seed));
+            let outcome = solve_board_with(&board, DEFAULT_MOVES, &config);
+            outcome.solution().expect("should solve board")
+        };
+        let fixed = solve_board(&board, DEFAULT_MOVES).unwrap();
+        assert_eq!(
+            fixed,
+            solve_board(&board, DEFAULT_MOVES).unwrap(),
+            "should be reproducible"
+        );
+        let (a, b) = (seeded(0), seeded(3));
+        assert_ne!(a, b, "should find different solutions for different seeds");
+        assert_eq!(a, seeded(0), "should be reproducible for a seed");
+        assert_eq!(a.len(), fixed.len());
+        assert_eq!(b.len(), fixed.len());
+        assert!(verify_solution(&board, &a) && verify_solution(&board, &b));
+    }
+
+    #[test]
+    #[cfg(feature = "diagonals")]
+    fn solve_diagonal() {
+        let board: Board = "3 0 0 0|0 3 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert!(!board.is_lost(), "should clear along the diagonal");
+        assert_eq!(solve_board(&board, 5), Some(vec![Action::DOWNRIGHT]));
+        let apart: Board = "0 0 0 0|0 0 0 0|0 0 3 0|0 0 0 3".parse().unwrap();
+        let solution = solve_board(&apart, 8).expect("should be solvable");
+        assert_eq!(solution, vec![Action::DOWNRIGHT; 3]);
+        assert!(verify_solution(&apart, &solution));
+        assert!(apart.heuristic() <= solution.len());
+    }
+
+    #[test]
+    fn best_reachable_board() {
+        let unsolvable: Board = "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let (solution, best, prefix) = solve_or_best(&unsolvable, 6);
+        assert_eq!(solution, None);
+        assert!(!best.is_lost());
+        assert!(best.score() >= unsolvable.score());
+        assert_eq!(unsolvable.apply_actions_checked(&prefix), Ok(best));
+        let route = solve_board_with(
+            &unsolvable,
+            prefix.len(),
+            &SolverConfig::default().win_condition(WinCondition::Target(best)),
+        )
+        .solution()
+        .expect("should reach the best board");
+        assert_eq!(route.len(), prefix.len(), "should take the fewest moves");
+
+        let partial: Board = "3 3 0 0|0 0 0 0|0 0 0 0|0 1 2 0".parse().unwrap();
+        let (solution, best, prefix) = solve_or_best(&partial, 3);
+        assert_eq!(solution, None, "should need more moves");
+        assert_eq!(best, partial.action(Action::RIGHT).unwrap());
+        assert_eq!(prefix, vec![Action::RIGHT]);
+        assert_eq!(solve_or_best(&partial, 3).2, prefix, "should be stable");
+
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let (solution, best, prefix) = solve_or_best(&board, 8);
+        assert_eq!(solution, solve_board(&board, 8));
+        assert!(best.is_won());
+        assert_eq!(Some(prefix), solution);
+    }
+
+    #[test]
+    fn solution_within_budget() {
+        for (board, max_moves) in [
+            ("0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0", 4),
+            ("0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0", 10),
+            ("4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0", 1),
+            ("0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0", 8),
+            ("0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0", 6),
+        ] {
+            let board: Board = board.parse().unwrap();
+            let solution = solve_board(&board, max_moves).expect("should be solvable");
+            assert!(
+                solution.len() <= max_moves,
+                "should fit into {max_moves} moves"
+            );
+            assert!(verify_solution(&board, &solution));
+            assert_eq!(
+                solve_board(&board, solution.len() - 1),
+                None,
+                "should be a shortest solution of {board}"
+            );
+        }
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        assert_eq!(solve_board(&board, 3), None);
+    }
+
+    #[test]
+    fn order_children_by_clears() {
+        let board: Board = "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let step = SolveStep {
+            board,
+            seq: ActionSequence::new(),
+        };
+        let visited = HashSet::new();
+        let first = |config: &SolverConfig| expand(step, &visited, config).next().unwrap();
+        assert_eq!(first(&SolverConfig::default()).seq.get(0), Action::DOWN);
+        let ordered = SolverConfig::default().order_by_clears(true);
+        assert!(
+            first(&ordered).board.is_won(),
+            "should expand the clearing move first"
+        );
+        for board in [
+            "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0",
+            "0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0",
+            "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0",
+        ] {
+            let board: Board = board.parse().unwrap();
+            let solution = solve_board_with(&board, 10, &ordered).solution().unwrap();
+            assert!(verify_solution(&board, &solution));
+            assert_eq!(Some(solution.len()), optimal_moves(&board, 10));
+        }
+    }
+
+    #[test]
+    fn measure_component_diameter() {
+        for board in [
+            "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0",
+            "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0",
+            "0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0",
+        ] {
+            let board: Board = board.parse().unwrap();
+            let k = optimal_moves(&board, 10).unwrap();
+            assert!(component_diameter(&board, 10) >= k);
+        }
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        assert_eq!(
+            component_diameter(&board, 2),
+            2,
+            "should cap at the max depth"
+        );
+        assert_eq!(component_diameter(&board, 0), 0);
+        let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(
+            component_diameter(&won, 5),
+            0,
+            "should not leave a won board"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn best_start_off_walls() {
+        let won: Board = "W 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(solve_best_start(&won, 5), Some((Point::from(0, 1), vec![])));
+        let board: Board = "W 3 3 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let (start, solution) = solve_best_start(&board, 5).unwrap();
+        assert!(!board.is_wall(start));
+        assert!(verify_solution(&board.clone_with_cursor(start), &solution));
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn solve_with_wall() {
+        let board: Board = "4 0 W 4|0 0 0 0|0 0 0 0|4 0 0 4".parse().unwrap();
+        let blocked = board.action(Action::RIGHT).unwrap();
+        assert!(
+            blocked.same_cells(&board),
+            "should stop the sweep at the wall"
+        );
+        let solution = solve_board(&board, 10).expect("should be solvable around the wall");
+        assert!(verify_solution(&board, &solution));
+        let end = solution.iter().fold(board, |b, a| b.action(*a).unwrap());
+        assert!(end.is_won() && end.is_wall(Point::from(0, 2)));
+        let split: Board = "3 W 3 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert!(split.is_lost(), "should not clear across the wall");
+        let trapped: Board = "0 W 3 3|W W 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(
+            solve_board_with(&trapped, 10, &SolverConfig::default()),
+            SolveOutcome::Unsolvable,
+            "should prune the trapped cursor"
+        );
+    }
+
+    #[test]
+    fn persist_outcomes() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let solved = solve_board_with(&board, 10, &SolverConfig::default());
+        let bytes = solved.to_bytes(10).unwrap();
+        assert_eq!(SolveOutcome::from_bytes(&bytes, 10), Some(solved.clone()));
+        assert_eq!(SolveOutcome::from_bytes(&bytes, 20), Some(solved));
+        assert_eq!(
+            SolveOutcome::from_bytes(&bytes, 3),
+            Some(SolveOutcome::Unsolvable),
+            "should be unsolvable below the shortest solution"
+        );
+        assert_eq!(SolveOutcome::from_bytes(&bytes[..5], 10), None);
+
+        let bytes = SolveOutcome::Unsolvable.to_bytes(8).unwrap();
+        assert_eq!(
+            SolveOutcome::from_bytes(&bytes, 8),
+            Some(SolveOutcome::Unsolvable)
+        );
+        assert_eq!(
+            SolveOutcome::from_bytes(&bytes, 9),
+            None,
+            "should search again with a larger budget"
+        );
+        assert_eq!(SolveOutcome::LimitReached.to_bytes(8), None);
+
+        let db: HashMap<_, _> = [(board.to_bytes(), bytes)].into_iter().collect();
+        assert_eq!(
+            SolveOutcome::from_bytes(&db[&board.to_bytes()], 5),
+            Some(SolveOutcome::Unsolvable)
+        );
+    }
+
+    #[test]
+    fn record_layer_sizes() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let sizes = Arc::new(Mutex::new(vec![]));
+        let config = SolverConfig::default().record_layer_sizes(Some(sizes.clone()));
+        let solution = solve_board_with(&board, 10, &config).solution().unwrap();
+        assert_eq!(solution, solve_board(&board, 10).unwrap());
+        let sizes = sizes.lock().unwrap().clone();
+        assert_eq!(sizes.len(), solution.len());
+
+        // Count the new boards of every layer independently
+        let mut visited = HashSet::from([board]);
+        let mut layer = vec![board];
+        for size in sizes {
+            layer = layer
+                .iter()
+                .flat_map(|b| b.children())
+                .filter(|b| visited.insert(*b))
+                .collect();
+            assert_eq!(layer.len(), size);
+        }
+    }
+
+    #[test]
+    fn rotate_solutions() {
+        let board: Board = "0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0".parse().unwrap();
+        let solution = solve_board(&board, 6).unwrap();
+        let rotated = rotate_solution(&solution, Transform::Deg90);
+        assert_eq!(rotate_solution(&rotated, Transform::Deg270), solution);
+        assert_eq!(
+            reverse_rotate_solution(&rotated, Transform::Deg90),
+            solution
+        );
+        for t in Transform::ALL {
+            let transformed = board.symmetry(t);
+            let moved = rotate_solution(&solution, t);
+            assert!(
+                verify_solution(&transformed, &moved),
+                "should solve the board transformed by {t:?}"
+            );
+            assert_eq!(reverse_rotate_solution(&moved, t), solution);
+        }
+    }
+
+    #[test]
+    fn generate_board_of_length() {
+        for (seed, target_moves) in [(1, 1), (2, 3), (3, 5)] {
+            let board = generate_hard_board(seed, target_moves).expect("should find a board");
+            let solution = solve_board(&board, ActionSequence::MAX_LENGTH).unwrap();
+            assert_eq!(
+                solution.len(),
+                target_moves,
+                "should need {target_moves} moves for {board}"
+            );
+        }
+        assert_eq!(generate_hard_board(4, 0).map(|b| b.is_won()), Some(true));
+    }
+
+    #[test]
+    #[cfg(feature = "walls")]
+    fn generate_walled_board() {
+        let walled: Board = "0 0 0 0|0 W 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let board =
+            generate_hard_board_with_walls(1, 3, walled.walls()).expect("should find a board");
+        assert_eq!(board.walls(), walled.walls());
+        assert_eq!(
+            solve_board(&board, ActionSequence::MAX_LENGTH).map(|s| s.len()),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn solve_won_board() {
+        let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(solve_board(&won, 0), Some(vec![]));
+        // Solved before the search expands or even counts a board
+        let config = SolverConfig::default()
+            .visited_limit(Some(0))
+            .abort(Some(Arc::new(AtomicBool::new(true))));
+        assert_eq!(
+            solve_board_with(&won, DEFAULT_MOVES, &config),
+            SolveOutcome::Solved(vec![])
+        );
+    }
+
+    #[test]
+    fn walk_zero_cells_optimally() {
+        // There is no shortcut for walks over zero cells: each step is a move of its own,
+        // so the search finds the same lengths as the independent iterative deepening
+        for (board_str, cursor) in [
+            ("0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0", Point::from(0, 3)),
+            ("0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0", Point::from(3, 3)),
+            ("0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0", Point::from(3, 0)),
+        ] {
+            let board: Board = board_str.parse().unwrap();
+            let board = board.clone_with_cursor(cursor);
+            let solution = solve_board(&board, 10).expect("should walk to the nonzero cells");
+            assert_eq!(
+                Iddfs.solve(&board, 10).map(|s| s.len()),
+                Some(solution.len()),
+                "{board}"
+            );
+        }
+    }
+
+    #[test]
+    fn explore_from_zero_cursor() {
+        // No move from the corner clears a cell, yet walking over zero cells reaches the 3s
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let corner = board.clone_with_cursor(Point::from(0, N - 1));
+        assert!(corner.best_single_move().is_none());
+        let solution = solve_board(&corner, 10).expect("should walk to the 3s");
+        let moves = if cfg!(feature = "diagonals") { 4 } else { 6 };
+        assert_eq!(solution.len(), moves);
+        assert!(verify_solution(&corner, &solution));
+        assert_eq!(Iddfs.solve(&corner, 10).map(|s| s.len()), Some(moves));
+
+        let stuck: Board = "0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        let stuck = stuck.clone_with_cursor(Point::from(3, 3));
+        assert_eq!(
+            solve_board_with(&stuck, 12, &SolverConfig::default()),
+            SolveOutcome::Unsolvable
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::action::{ActionSequence, ACTIONS};
+use crate::board::Board;
+use crate::rng::Rng;
+use crate::solve::{rotate_solution, solve_board, verify_solution};
+use crate::values::Transform;
+use std::collections::HashSet;
+
+/**
+   The length of the shortest solution of the sample board
+   `18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0`.
+*/
+pub const SAMPLE_MOVES: usize = if cfg!(feature = "diagonals") { 11 } else { 13 };
+
+/**
+   Solve `board` and check that the solution transformed by `t` solves the
+   board transformed by `t`.
+   Panics with the board and transform if the board has no solution or the
+   transformed solution does not solve the transformed board.
+*/
+pub fn assert_symmetric_solution(board: &Board, t: Transform) {
+    let solution = solve_board(board, ActionSequence::MAX_LENGTH)
+        .unwrap_or_else(|| panic!("board should be solvable: {board}"));
+    let transformed = board.symmetry(t);
+    let moved = rotate_solution(&solution, t);
+    assert!(
+        verify_solution(&transformed, &moved),
+        "{t:?} of solution {solution:?} should solve {t:?} of {board}"
+    );
+}
+
+/**
+   How a `random_playout` ended.
+*/
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PlayoutEnd {
+    Won,
+    Lost,
+    /// Every move leaves the grid or returns to a board of the playout
+    Stuck,
+}
+
+/**
+   Play random moves from `board` which never return to a board of the playout,
+   giving how the game ended, the final board and the number of moves.
+   Even the plain rules let the cells return to earlier values without clearing
+   a cell, so only repeating a whole board counts as a loop and is avoided.
+   Panics with the board and seed if the game has not ended after `steps` moves,
+   which hints at a rule variant whose games need not end.
+*/
+pub fn random_playout(board: &Board, steps: usize, seed: u64) -> (PlayoutEnd, Board, usize) {
+    let mut rng = Rng::new(seed);
+    let mut seen = HashSet::from([*board]);
+    let mut current = *board;
+    for step in 0..=steps {
+        if current.is_won() {
+            return (PlayoutEnd::Won, current, step);
+        }
+        if current.is_lost() {
+            return (PlayoutEnd::Lost, current, step);
+        }
+        assert!(
+            step < steps,
+            "playout from {board} with seed {seed} should end within {steps} moves"
+        );
+        let moves: Vec<Board> = ACTIONS
+            .into_iter()
+            .filter_map(|a| current.action(a))
+            .filter(|b| !seen.contains(b))
+            .collect();
+        if moves.is_empty() {
+            return (PlayoutEnd::Stuck, current, step);
+        }
+        current = moves[rng.below(moves.len())];
+        seen.insert(current);
+    }
+    unreachable!("the last step should end or panic")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::Point;
+
+    #[test]
+    fn solve_sample_symmetries() {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        for t in Transform::ALL {
+            assert_symmetric_solution(&board, t);
+        }
+    }
+
+    #[test]
+    fn solve_symmetries_with_moved_cursor() {
+        let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+        let board = board.clone_with_cursor(Point::from(1, 2));
+        for t in Transform::ALL {
+            assert_symmetric_solution(&board, t);
+        }
+    }
+
+    #[test]
+    fn end_random_playouts() {
+        let board: Board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0".parse().unwrap();
+        for seed in 0..200 {
+            let (end, last, moves) = random_playout(&board, 1000, seed);
+            assert!(moves > 0);
+            match end {
+                PlayoutEnd::Won => assert!(last.is_won()),
+                PlayoutEnd::Lost => assert!(last.is_lost()),
+                PlayoutEnd::Stuck => assert!(!last.is_won() && !last.is_lost()),
+            }
+        }
+        let won: Board = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 0".parse().unwrap();
+        assert_eq!(random_playout(&won, 0, 1), (PlayoutEnd::Won, won, 0));
+    }
+}
@@ -26,6 +26,24 @@ impl Point {
     pub fn inside(&self) -> bool {
         self.index() < N * N
     }
+
+    pub fn manhattan_distance(&self, other: Point) -> usize {
+        self.row().abs_diff(other.row()) + self.column().abs_diff(other.column())
+    }
+
+    /**
+       The points of row `r` from left to right.
+    */
+    pub fn row_points(r: usize) -> impl Iterator<Item = Point> {
+        (0..N).map(move |c| Point::from(r, c))
+    }
+
+    /**
+       The points of column `c` from top to bottom.
+    */
+    pub fn col_points(c: usize) -> impl Iterator<Item = Point> {
+        (0..N).map(move |r| Point::from(r, c))
+    }
 }
 
 impl fmt::Display for Point {
@@ -33,3 +51,133 @@ impl fmt::Display for Point {
         write!(f, "Point[{},{}]", self.row(), self.column())
     }
 }
+
+/**
+   Symmetry of the square board as an element of the dihedral group:
+   an optional mirroring of the columns followed by a clockwise rotation.
+*/
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Transform {
+    Identity,
+    Deg90,
+    Deg180,
+    Deg270,
+    Mirror,
+    MirrorDeg90,
+    MirrorDeg180,
+    MirrorDeg270,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Deg90,
+        Transform::Deg180,
+        Transform::Deg270,
+        Transform::Mirror,
+        Transform::MirrorDeg90,
+        Transform::MirrorDeg180,
+        Transform::MirrorDeg270,
+    ];
+
+    fn from_parts(mirror: bool, quarter_turns: usize) -> Self {
+        Self::ALL[(mirror as usize) * 4 + quarter_turns % 4]
+    }
+
+    pub fn mirrored(&self) -> bool {
+        (*self as usize) >= 4
+    }
+
+    pub fn quarter_turns(&self) -> usize {
+        (*self as usize) % 4
+    }
+
+    /**
+       The transform which undoes this transform.
+    */
+    pub fn reverse(&self) -> Self {
+        if self.mirrored() {
+            // A mirrored rotation is its own inverse
+            *self
+        } else {
+            Self::from_parts(false, 4 - self.quarter_turns())
+        }
+    }
+
+    /**
+       The transform equivalent to applying `self` first and `other` second.
+    */
+    pub fn compose(&self, other: Transform) -> Self {
+        // Mirroring reverses the direction of the preceding rotation
+        let turns = if other.mirrored() {
+            4 + other.quarter_turns() - self.quarter_turns()
+        } else {
+            other.quarter_turns() + self.quarter_turns()
+        };
+        Self::from_parts(self.mirrored() != other.mirrored(), turns)
+    }
+}
+
+impl Point {
+    pub fn symmetry(&self, t: Transform) -> Self {
+        if !self.inside() {
+            return *self;
+        }
+        let (mut row, mut col) = (self.row(), self.column());
+        if t.mirrored() {
+            col = N - 1 - col;
+        }
+        for _ in 0..t.quarter_turns() {
+            (row, col) = (col, N - 1 - row);
+        }
+        Point::from(row, col)
+    }
+
+    pub fn reverse_symmetry(&self, t: Transform) -> Self {
+        self.symmetry(t.reverse())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_and_column_points() {
+        let row: Vec<Point> = Point::row_points(2).collect();
+        assert_eq!(
+            row,
+            (0..N).map(|c| Point::from(2, c)).collect::<Vec<_>>(),
+            "should be in column order"
+        );
+        assert!(row.iter().all(|p| p.row() == 2));
+        let col: Vec<usize> = Point::col_points(1).map(|p| p.row()).collect();
+        assert_eq!(col, vec![0, 1, 2, 3]);
+        assert!(Point::col_points(1).all(|p| p.column() == 1));
+    }
+
+    #[test]
+    fn compose_transforms() {
+        assert_eq!(
+            Transform::Deg90.compose(Transform::Deg90),
+            Transform::Deg180
+        );
+        assert_eq!(
+            Transform::Mirror.compose(Transform::Mirror),
+            Transform::Identity
+        );
+        let points = (0..N * N).map(|i| Point::from(i / N, i % N));
+        for p in points {
+            for a in Transform::ALL {
+                assert_eq!(p.symmetry(a).reverse_symmetry(a), p);
+                for b in Transform::ALL {
+                    assert_eq!(
+                        p.symmetry(a).symmetry(b),
+                        p.symmetry(a.compose(b)),
+                        "should compose {a:?} with {b:?} for {p}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,588 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use zoysii_solver::action::{Action, ActionSequence};
+use zoysii_solver::board::Board;
+use zoysii_solver::solve::{
+    nth_shortest_solution, solve_board_with, verify_solution, SolverConfig, DEFAULT_MOVES,
+};
+
+/// The solution of `0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0`, one move shorter with diagonal moves.
+const FOURS: &[&str] = if cfg!(feature = "diagonals") {
+    &["DownRight", "Right"]
+} else {
+    &["Down", "Right", "Right"]
+};
+
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_zoysii_solver"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("should start solver");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn skip_blank_lines() {
+    let output = run(
+        &["--stdin"],
+        "\n0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n   \n\n0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0\n\t\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!("Down,Down,Down,Right\n{}\n", FOURS.join(","))
+    );
+}
+
+#[test]
+fn reject_empty_board() {
+    let output = run(&["  "], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stderr(&output), "Invalid: Empty board!\n");
+}
+
+#[test]
+fn validate_only() {
+    let input =
+        "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n0 0 0|0 0 0 0\n\n0 0 0 0|0 300 0 0|0 0 0 0|0 0 0 0\n";
+    let output = run(&["--stdin", "--validate-only"], input);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        stdout(&output),
+        "Line 2: WrongCellCount (board does not have 4x4 cells)\n\
+         Line 4: InvalidCell (cell is not a number from 0 to 255)\n"
+    );
+    let valid = run(&["--validate-only", "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0"], "");
+    assert!(valid.status.success());
+    assert_eq!(stdout(&valid), "");
+    let blocks = run(
+        &["--stdin", "--block", "--validate-only"],
+        "0 0 0 0\n0 0 0 0\n0 0 0 0\n3 3 0 0\n\n4 4 0 0\n",
+    );
+    assert_eq!(blocks.status.code(), Some(2));
+    assert_eq!(
+        stdout(&blocks),
+        "Line 2: WrongCellCount (board does not have 4x4 cells)\n",
+        "should read boards like the solver"
+    );
+    let separated = run(
+        &["--board-sep", ";", "--validate-only"],
+        ";0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0;0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0",
+    );
+    assert!(separated.status.success(), "{}", stdout(&separated));
+    let budget = run(
+        &["--validate-only", "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0@13"],
+        "",
+    );
+    assert!(budget.status.success(), "{}", stdout(&budget));
+    assert_eq!(stdout(&budget), "");
+    let bad_budget = run(
+        &["--validate-only", "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0@x"],
+        "",
+    );
+    assert_eq!(bad_budget.status.code(), Some(2));
+    assert_eq!(
+        stdout(&bad_budget),
+        format!(
+            "Line 1: Invalid: Move budget '@x' must be a number up to {}!\n",
+            ActionSequence::MAX_LENGTH
+        )
+    );
+}
+
+#[test]
+fn reject_lost_board_strictly() {
+    let lost = "0 0 0 0|0 0 0 0|0 0 0 0|0 0 0 5";
+    let output = run(&["--strict", lost], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        stderr(&output),
+        format!("Invalid: Board {lost} is not a valid game state!\n")
+    );
+    let lenient = run(&[lost], "");
+    assert!(lenient.status.success());
+    assert_eq!(stdout(&lenient), "No solution!\n");
+    let valid = run(&["--strict", "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0"], "");
+    assert!(valid.status.success(), "{}", stderr(&valid));
+}
+
+#[test]
+fn split_boards_at_separator() {
+    let boards = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0;0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0";
+    let output = run(&["--board-sep", ";", boards], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "Solution with 4 moves: Down, Down, Down, Right\n\
+             Solution with {} moves: {}\n",
+            FOURS.len(),
+            FOURS.join(", ")
+        )
+    );
+    let stdin = run(&["--stdin", "--board-sep", ";"], &format!("{boards}\n"));
+    assert_eq!(
+        stdout(&stdin),
+        format!("Down,Down,Down,Right\n{}\n", FOURS.join(","))
+    );
+    let ambiguous = run(&["--board-sep", "|", boards], "");
+    assert_eq!(ambiguous.status.code(), Some(2));
+}
+
+#[test]
+fn limit_visited_states() {
+    let board = "18 9 6 0|0 9 3 0|33 18 18 3|0 0 15 0";
+    let output = run(&["--limit-visited", "50", board], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "Search limit reached!\n");
+    let stdin = run(&["--stdin", "--limit-visited", "50"], &format!("{board}\n"));
+    assert_eq!(stdout(&stdin), "?\n");
+}
+
+#[test]
+fn replay_colors() {
+    let board = "0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0";
+    let plain = run(&["--replay", "--color", "never", board], "");
+    assert!(
+        !stdout(&plain).contains('\x1b'),
+        "should not contain escape codes"
+    );
+    assert_eq!(
+        stdout(&plain).lines().take(6).collect::<Vec<_>>(),
+        [
+            format!("Solution with {} moves: {}", FOURS.len(), FOURS.join(", ")).as_str(),
+            "[  0]   0    0    0",
+            "   0    4    4    0",
+            "   0    0    0    0",
+            "   0    0    0    0",
+            &format!("{}:", FOURS[0]),
+        ]
+    );
+    let colored = run(&["--replay", "--color", "always", board], "");
+    assert!(stdout(&colored).contains("\x1b[7m[  0]\x1b[0m"));
+    assert!(stdout(&colored).contains("\x1b[1;33m  4\x1b[0m"));
+    // Not a terminal
+    let auto = run(&["--replay", board], "");
+    assert_eq!(stdout(&auto), stdout(&plain));
+}
+
+#[test]
+fn batch_summary() {
+    let input = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n\
+        0 0 0 0|0 0 0 0|0 0 0 0|3 0 0 0\n\
+        not a board\n\
+        0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0\n";
+    let output = run(&["--stdin", "--summary", "--keep-going"], input);
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "should fail for parse errors"
+    );
+    assert_eq!(
+        stdout(&output),
+        format!("Down,Down,Down,Right\nX\n{}\n", FOURS.join(","))
+    );
+    let err = stderr(&output);
+    assert!(
+        err.contains(
+            "Summary: 4 boards, 2 solved, 1 unsolvable, 0 search limit reached, 1 parse errors in "
+        ),
+        "{err}"
+    );
+    let stopped = run(&["--stdin", "--summary"], input);
+    assert_eq!(stopped.status.code(), Some(2));
+    assert_eq!(
+        stdout(&stopped),
+        "Down,Down,Down,Right\nX\n",
+        "should stop at the malformed board without --keep-going"
+    );
+}
+
+#[test]
+fn override_budget_per_board() {
+    let board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0";
+    let output = run(&["--moves", "3", &format!("{board}@4"), board], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "Solution with 4 moves: Down, Down, Down, Right\nNo solution!\n"
+    );
+    let invalid = run(&[&format!("{board}@many")], "");
+    assert_eq!(invalid.status.code(), Some(2));
+    assert!(stderr(&invalid).contains("'@many'"));
+    let too_long = run(&[&format!("{board}@30")], "");
+    assert_eq!(too_long.status.code(), Some(2));
+}
+
+#[test]
+fn count_only() {
+    let board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0";
+    let output = run(&["--count-only", &format!("{board}@3"), board], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "X\n4\n");
+    let batch = run(
+        &["--count-only", "--stdin", "--moves", "10"],
+        &format!("{board}\n0 5 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n"),
+    );
+    assert!(batch.status.success(), "{}", stderr(&batch));
+    assert_eq!(stdout(&batch), "4\nX\n");
+}
+
+#[test]
+fn select_algorithm() {
+    let board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0";
+    for algo in ["bfs", "iddfs"] {
+        let output = run(&["--algo", algo, board, &format!("{board}@3")], "");
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(
+            stdout(&output),
+            "Solution with 4 moves: Down, Down, Down, Right\nNo solution!\n"
+        );
+    }
+}
+
+#[test]
+fn collapse_walks_in_json() {
+    let board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0";
+    let output = run(&["--json", board, &format!("{board}@3")], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{{\"board\":\"{board}\",\"outcome\":\"solved\",\"moves\":[\"Down\",\"Down\",\"Down\",\"Right\"]}}\n\
+             {{\"board\":\"{board}\",\"outcome\":\"unsolvable\"}}\n"
+        )
+    );
+    let collapsed = run(&["--json", "--collapse-walks", board], "");
+    assert_eq!(
+        stdout(&collapsed),
+        format!("{{\"board\":\"{board}\",\"outcome\":\"solved\",\"moves\":[{{\"goto\":[3,0]}},\"Right\"]}}\n")
+    );
+    let text = run(&["--collapse-walks", board], "");
+    assert!(!text.status.success(), "should require --json");
+}
+
+#[test]
+fn auto_moves() {
+    let output = run(
+        &["--auto-moves", "--stdin", "--count-only"],
+        "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n\
+         0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n\
+         0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let symmetric = if cfg!(feature = "diagonals") { 4 } else { 6 };
+    assert_eq!(stdout(&output), format!("1\n4\n{symmetric}\n"));
+    let conflict = run(&["--auto-moves", "--moves", "5", "--stdin"], "");
+    assert!(!conflict.status.success(), "should conflict with --moves");
+}
+
+#[test]
+fn read_board_blocks() {
+    let output = run(
+        &["--stdin", "--block", "--moves", "10"],
+        "0 0 0 0\n0  0 0 0\n0 0 0 0\n3 3\t0 0\n\n\n4 4 0 0\n0 0 0 0\n0 0 0 0\n0 0 0 0\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let joined = run(
+        &["--stdin", "--moves", "10"],
+        "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n",
+    );
+    assert_eq!(stdout(&output), stdout(&joined));
+    assert_eq!(stdout(&output), "Down,Down,Down,Right\nRight\n");
+    let short = run(&["--stdin", "--block"], "0 0 0 0\n3 3 0 0\n\n");
+    assert_eq!(
+        short.status.code(),
+        Some(2),
+        "should reject incomplete block"
+    );
+}
+
+#[test]
+fn find_hardest_board() {
+    let output = run(
+        &["--find-hardest", "--stdin", "--moves", "10"],
+        "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n\
+         0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0\n\
+         0 5 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n\
+         0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let out = stdout(&output);
+    // With diagonal moves, it ties with the last board but comes first
+    let moves = if cfg!(feature = "diagonals") { 4 } else { 6 };
+    assert!(
+        out.starts_with(&format!(
+            "Hardest board 0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0 with {moves} moves ("
+        )),
+        "{out}"
+    );
+    assert_eq!(out.lines().count(), 1);
+}
+
+#[test]
+fn skip_comment_lines() {
+    let input = "# Level pack\n\n0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n  # easy one next\n4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n\n";
+    let output = run(&["--stdin", "--moves", "10"], input);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Down,Down,Down,Right\nRight\n");
+    let validated = run(&["--stdin", "--validate-only"], input);
+    assert!(validated.status.success(), "{}", stdout(&validated));
+    let blocks = run(
+        &["--stdin", "--block", "--moves", "10"],
+        "# first\n4 4 0 0\n0 0 0 0\n# still first\n0 0 0 0\n0 0 0 0\n",
+    );
+    assert_eq!(stdout(&blocks), "Right\n");
+}
+
+#[test]
+fn packed_format() {
+    let board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0";
+    let output = run(&["--format", "packed", board, &format!("{board}@3")], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let out = stdout(&output);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], "X");
+    let bits = u64::from_str_radix(lines[0].trim_start_matches("0x"), 16).unwrap();
+    let seq = ActionSequence::from_bits(bits).expect("should decode packed solution");
+    assert_eq!(
+        Vec::<Action>::from(seq),
+        vec![Action::DOWN, Action::DOWN, Action::DOWN, Action::RIGHT]
+    );
+    let json = run(&["--format", "json", "--collapse-walks", board], "");
+    assert!(stdout(&json).contains("{\"goto\":[3,0]}"));
+}
+
+#[test]
+fn json_out_file() {
+    let path = std::env::temp_dir().join(format!("zoysii_json_out_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let json_out = path.to_str().unwrap();
+    let input = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0\n";
+    let output = run(&["--stdin", "--json-out", json_out], input);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Down,Down,Down,Right\nX\n");
+    let json = run(&["--stdin", "--json"], input);
+    let solved = stdout(&json).lines().next().unwrap().to_string();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        format!("{solved}\n")
+    );
+
+    let output = run(
+        &["--json-out", json_out, "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0"],
+        "",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Solution with 1 moves: Right\n");
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(written.lines().count(), 2, "should append to the file");
+    assert!(written.ends_with(
+        "{\"board\":\"4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\",\"outcome\":\"solved\",\"moves\":[\"Right\"]}\n"
+    ));
+}
+
+#[test]
+fn resume_from_checkpoint() {
+    let path = std::env::temp_dir().join(format!("zoysii_checkpoint_{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let checkpoint = path.to_str().unwrap();
+    let args = ["--stdin", "--summary", "--checkpoint", checkpoint];
+    let first = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n# comment\n0 0 0 0|0 1 2 0|0 0 0 0|0 0 0 0\n";
+    let third = "4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n";
+
+    // The first run stops after its second board, e.g. when it crashed
+    let output = run(&args, first);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Down,Down,Down,Right\nX\n");
+
+    let resume = [&args[..], &["--resume"]].concat();
+    let output = run(&resume, &format!("{first}{third}"));
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Right\n", "should skip the solved lines");
+    assert!(stderr(&output).contains("Summary: 3 boards, 2 solved, 1 unsolvable"));
+
+    let changed = run(&resume, &format!("{third}{first}{third}"));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(changed.status.code(), Some(2));
+    assert!(stderr(&changed).contains("Input differs from the checkpoint"));
+    assert_eq!(stdout(&changed), "");
+}
+
+#[test]
+fn warn_at_budget() {
+    let board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0";
+    let output = run(&[&format!("{board}@4")], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "Solution with 4 moves: Down, Down, Down, Right\n"
+    );
+    assert!(stderr(&output).contains("Warning: Solution takes the whole budget of 4 moves"));
+    assert!(stderr(&output).contains("no shorter solution exists"));
+    let batch = run(&["--stdin", "--moves", "4"], board);
+    assert!(stderr(&batch).contains("whole budget of 4 moves"));
+    let roomy = run(&[&format!("{board}@5")], "");
+    assert_eq!(stderr(&roomy), "");
+}
+
+#[test]
+fn report_cache_stats() {
+    let input = "0 3 0 0|3 0 0 0|3 3 0 0|0 0 0 0\n0 3 3 0|3 0 3 0|0 0 0 0|0 0 0 0\n";
+    let output = run(&["--stdin", "--cache-stats"], input);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).lines().count(), 2);
+    assert!(stderr(&output).contains("Cache: 0 hits, 1 symmetric hits, 1 misses"));
+}
+
+#[test]
+fn profile_search_time() {
+    let input = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n";
+    let output = run(&["--stdin", "--profile"], input);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let err = stderr(&output);
+    assert_eq!(err.matches("Profile: ").count(), 1, "{err}");
+    let profile = err.lines().find(|l| l.starts_with("Profile: ")).unwrap();
+    let phases: Vec<&str> = profile
+        .trim_start_matches("Profile: ")
+        .split(", ")
+        .map(|phase| phase.rsplit_once(" in ").unwrap().0)
+        .collect();
+    assert_eq!(
+        phases,
+        ["Setup", "searched 2 boards", "reading and printing"],
+        "{err}"
+    );
+    let single = run(&["--profile", "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0"], "");
+    let err = stderr(&single);
+    assert_eq!(err.matches("Setup in ").count(), 1, "{err}");
+    assert!(err.contains(", searched 1 boards in "), "{err}");
+}
+
+#[test]
+fn limit_boards() {
+    let input = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n\
+        0 0 0 0|0 0 0 0|0 0 0 0|3 0 0 0\n\
+        not a board\n\
+        0 0 0 0|0 4 4 0|0 0 0 0|0 0 0 0\n\
+        4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n";
+    let output = run(&["--stdin", "--summary", "--limit", "2"], input);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Down,Down,Down,Right\nX\n");
+    assert!(stderr(&output).contains(
+        "Summary: 2 boards, 1 solved, 1 unsolvable, 0 search limit reached, 0 parse errors in "
+    ));
+}
+
+#[test]
+fn print_layer_sizes() {
+    let board: Board = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0".parse().unwrap();
+    let sizes = Arc::new(Mutex::new(vec![]));
+    let config = SolverConfig::default().record_layer_sizes(Some(sizes.clone()));
+    assert!(solve_board_with(&board, DEFAULT_MOVES, &config)
+        .solution()
+        .is_some());
+    let output = run(&["--layer-sizes", "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let expected = sizes
+        .lock()
+        .unwrap()
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(expected.len(), 4);
+    assert!(
+        stderr(&output).contains(&format!("Layers: {}\n", expected.join(","))),
+        "{}",
+        stderr(&output)
+    );
+    let iddfs = run(
+        &[
+            "--layer-sizes",
+            "--algo",
+            "iddfs",
+            "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0",
+        ],
+        "",
+    );
+    assert_eq!(iddfs.status.code(), Some(1));
+    assert_eq!(stderr(&iddfs), "Invalid: --layer-sizes needs --algo bfs!\n");
+}
+
+#[test]
+fn pick_solution_index() {
+    let board_str = "0 3 3 0|3 0 0 0|3 0 0 0|0 0 0 0";
+    let board: Board = board_str.parse().unwrap();
+    let line = |index| {
+        let actions = nth_shortest_solution(&board, DEFAULT_MOVES, index).unwrap();
+        assert!(verify_solution(&board, &actions));
+        let names: Vec<String> = actions.iter().map(Action::to_string).collect();
+        format!(
+            "Solution with {} moves: {}\n",
+            actions.len(),
+            names.join(", ")
+        )
+    };
+    let first = run(&["--solution-index", "0", board_str], "");
+    assert_eq!(stdout(&first), line(0));
+    assert_eq!(stdout(&run(&[board_str], "")), line(0));
+    let second = run(&["--solution-index", "1", board_str], "");
+    assert_eq!(stdout(&second), line(1));
+    assert_ne!(line(0), line(1));
+    let beyond = run(&["--solution-index", "1000000", board_str], "");
+    assert_eq!(beyond.status.code(), Some(2));
+    assert!(stderr(&beyond).contains("shortest solutions!"));
+}
+
+#[test]
+fn unsolved_out_file() {
+    let path = std::env::temp_dir().join(format!("zoysii_unsolved_{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let input = "0 0 0 0|0 0 0 0|0 0 0 0|3 3 0 0\n\
+        0 0 0 0|0 0 0 0|0 0 0 0|3 0 0 0\n\
+        not a board\n\
+        4 4 0 0|0 0 0 0|0 0 0 0|0 0 0 0\n";
+    let args = [
+        "--stdin",
+        "--summary",
+        "--keep-going",
+        "--unsolved-out",
+        path.to_str().unwrap(),
+    ];
+    let output = run(&args, input);
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "should fail for parse errors"
+    );
+    assert_eq!(stdout(&output), "Down,Down,Down,Right\nRight\n");
+    let unsolved = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        unsolved,
+        format!(
+            "# Unsolvable within {DEFAULT_MOVES} moves\n0 0 0 0|0 0 0 0|0 0 0 0|3 0 0 0\n\
+             # Failed to parse board\nnot a board\n"
+        )
+    );
+}